@@ -5,4 +5,5 @@ fn main() {
     println!("cargo::rustc-env=CH_HOST={}", env::var("HOST").unwrap());
     println!("cargo::rustc-env=CH_TARGET={}", env::var("TARGET").unwrap());
     println!("cargo::rustc-env=CH_BUILDSCRIPT=rev-1");
+    println!("cargo::rerun-if-changed=migrations");
 }
\ No newline at end of file
@@ -0,0 +1,8 @@
+-- Conlang channels. Existing databases already have this table, so it is only created if it is missing.
+CREATE TABLE IF NOT EXISTS Channels (
+    ID BIGINT UNSIGNED NOT NULL,
+    Owner BIGINT UNSIGNED NOT NULL,
+    Category BIGINT UNSIGNED NULL,
+    PRIMARY KEY (ID),
+    INDEX (Owner)
+) DEFAULT CHARSET = utf8mb4;
@@ -0,0 +1,13 @@
+-- Saved /wordgen settings. Channel is set while the preset is shared in that channel.
+CREATE TABLE IF NOT EXISTS WordgenPresets (
+    Owner BIGINT UNSIGNED NOT NULL,
+    Name VARCHAR(64) NOT NULL,
+    Channel BIGINT UNSIGNED NULL,
+    MinSyllables BIGINT NOT NULL,
+    MaxSyllables BIGINT NOT NULL,
+    Syllable TEXT NOT NULL,
+    Categories TEXT NOT NULL,
+    Filter TEXT NULL,
+    PRIMARY KEY (Owner, Name),
+    INDEX (Channel, Name)
+) DEFAULT CHARSET = utf8mb4 COLLATE = utf8mb4_bin;
@@ -0,0 +1,14 @@
+-- Lexicon entries of conlang channels. Created and Updated are unix timestamps.
+CREATE TABLE IF NOT EXISTS Lexicon (
+    ID BIGINT UNSIGNED NOT NULL AUTO_INCREMENT,
+    Channel BIGINT UNSIGNED NOT NULL,
+    Headword VARCHAR(100) NOT NULL,
+    Pronunciation VARCHAR(100) NULL,
+    PartOfSpeech VARCHAR(50) NULL,
+    Glosses TEXT COLLATE utf8mb4_unicode_ci NOT NULL,
+    Notes TEXT NULL,
+    Created BIGINT NOT NULL,
+    Updated BIGINT NOT NULL,
+    PRIMARY KEY (ID),
+    INDEX (Channel, Headword)
+) DEFAULT CHARSET = utf8mb4 COLLATE = utf8mb4_bin;
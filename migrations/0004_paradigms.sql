@@ -0,0 +1,7 @@
+-- Inflection paradigms of conlang channels, stored in the text format of /paradigm set.
+CREATE TABLE IF NOT EXISTS Paradigms (
+    Channel BIGINT UNSIGNED NOT NULL,
+    Name VARCHAR(64) NOT NULL,
+    Definition TEXT NOT NULL,
+    PRIMARY KEY (Channel, Name)
+) DEFAULT CHARSET = utf8mb4 COLLATE = utf8mb4_bin;
@@ -0,0 +1,7 @@
+-- Phoneme inventories of conlang channels as space separated IPA symbols.
+CREATE TABLE IF NOT EXISTS Inventories (
+    Channel BIGINT UNSIGNED NOT NULL,
+    Consonants TEXT NOT NULL,
+    Vowels TEXT NOT NULL,
+    PRIMARY KEY (Channel)
+) DEFAULT CHARSET = utf8mb4 COLLATE = utf8mb4_bin;
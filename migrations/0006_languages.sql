@@ -0,0 +1,14 @@
+-- Language profiles of conlang channels shown by /lang info. Updated is a unix timestamp.
+CREATE TABLE IF NOT EXISTS Languages (
+    Channel BIGINT UNSIGNED NOT NULL,
+    Name VARCHAR(100) NOT NULL,
+    Autonym VARCHAR(100) NULL,
+    Type VARCHAR(100) NULL,
+    WordOrder VARCHAR(50) NULL,
+    Alignment VARCHAR(50) NULL,
+    Morphology VARCHAR(50) NULL,
+    Status VARCHAR(50) NULL,
+    Description TEXT NULL,
+    Updated BIGINT NOT NULL,
+    PRIMARY KEY (Channel)
+) DEFAULT CHARSET = utf8mb4;
@@ -0,0 +1,3 @@
+-- The paradigm a lexicon entry inflects with. It has to come after Updated because entries are read by column
+-- position, and defaults to NULL for rows added without one, like those of /word import.
+ALTER TABLE Lexicon ADD COLUMN Paradigm VARCHAR(64) NULL DEFAULT NULL AFTER Updated;
@@ -0,0 +1,19 @@
+-- Presets store the whole definition file instead of a fixed set of settings, so they can hold syllable patterns
+-- per position, orthography, stress and tones. Existing presets are converted to the definition format.
+ALTER TABLE WordgenPresets ADD COLUMN Definition TEXT NULL AFTER Channel;
+
+UPDATE WordgenPresets SET Definition = CONCAT(
+    'min-syllables = ', MinSyllables, '\n',
+    'max-syllables = ', MaxSyllables, '\n',
+    'syllable = ', Syllable, '\n',
+    IF(Filter IS NULL, '', CONCAT('filter = ', Filter, '\n')),
+    REPLACE(Categories, ';', '\n'), '\n'
+);
+
+ALTER TABLE WordgenPresets
+    DROP COLUMN MinSyllables,
+    DROP COLUMN MaxSyllables,
+    DROP COLUMN Syllable,
+    DROP COLUMN Categories,
+    DROP COLUMN Filter,
+    MODIFY Definition TEXT NOT NULL;
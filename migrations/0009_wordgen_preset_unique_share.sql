@@ -0,0 +1,9 @@
+-- Only one preset with a given name can be shared in a channel, so /wordgen preset:<name> is never ambiguous.
+-- Presets that would collide stay with the owner that sorts first; the others are unshared.
+UPDATE WordgenPresets AS Preset
+    JOIN WordgenPresets AS Other ON Other.Channel = Preset.Channel AND Other.Name = Preset.Name AND Other.Owner < Preset.Owner
+SET Preset.Channel = NULL;
+
+ALTER TABLE WordgenPresets
+    DROP INDEX Channel,
+    ADD UNIQUE INDEX Channel (Channel, Name);
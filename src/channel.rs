@@ -1,7 +1,7 @@
 use crate::database::{add_channel, get_channels_by_owner};
 use crate::profile::{Category, Profile};
 use crate::DatabasePoolKey;
-use serenity::all::{ChannelId, ChannelType, CreateChannel, GuildChannel, Member, PermissionOverwrite, PermissionOverwriteType, User, UserId};
+use serenity::all::{ChannelId, ChannelType, CreateChannel, GuildChannel, Member, PermissionOverwrite, PermissionOverwriteType, RoleId, User, UserId};
 use serenity::client::Context;
 use serenity::model::Permissions;
 use sqlx::Error;
@@ -122,6 +122,19 @@ impl ConChannel {
         false
     }
 
+    /// Like `check_permission`, but also allows contributors added with /contributor.
+    pub async fn check_contributor(&self, ctx: &Context, user: &User, invoker: &Option<Box<Member>>) -> bool {
+        if self.check_permission(user, invoker) {
+            return true;
+        }
+        match self.id.to_channel(ctx).await.map(|channel| channel.guild()) {
+            Ok(Some(channel)) => channel.permission_overwrites.iter().any(|overwrite| {
+                overwrite.kind == PermissionOverwriteType::Member(user.id) && overwrite.allow.contains(Permissions::SEND_MESSAGES)
+            }),
+            _ => false
+        }
+    }
+
     pub fn check_permission_unboxed(&self, user: &User, invoker: &Option<Member>) -> bool {
         if self.owner == user.id {
             return true;
@@ -135,6 +148,18 @@ impl ConChannel {
     }
 }
 
+/// Whether /mode made the channel public or visible, or the user can view it as owner, viewer or contributor.
+/// A user banned with /ban can not view the channel, even if it is public.
+pub fn is_visible_to(channel: &GuildChannel, member_role: RoleId, user: UserId) -> bool {
+    let banned = channel.permission_overwrites.iter().any(|overwrite| {
+        overwrite.kind == PermissionOverwriteType::Member(user) && overwrite.deny.contains(Permissions::VIEW_CHANNEL)
+    });
+    !banned && channel.permission_overwrites.iter().any(|overwrite| {
+        (overwrite.kind == PermissionOverwriteType::Role(member_role) || overwrite.kind == PermissionOverwriteType::Member(user))
+            && overwrite.allow.contains(Permissions::VIEW_CHANNEL)
+    })
+}
+
 pub async fn check_channel_count(user: UserId, ctx: &Context) -> Result<bool, Error> {
     let data = ctx.data.read().await;
     let database_pool = data.get::<DatabasePoolKey>().unwrap();
@@ -154,7 +179,7 @@ pub async fn get_channels_in_category(category: u64, ctx: &Context) -> Result<Ve
     let guild = profile.guild();
     let category = ChannelId::new(category);
     let channels = guild.channels(&ctx).await.map_err(|err| format!("Failed to get channels: {err}"))?;
-    let channels: Vec<GuildChannel> = channels.into_iter().filter_map(|(_, channel)| {
+    let channels: Vec<GuildChannel> = channels.into_values().filter_map(|channel| {
         if channel.parent_id == Some(category) {
             Some(channel)
         } else {
@@ -221,4 +246,35 @@ pub async fn create_channel(creator: UserId, channel_name: &str, channel_topic:
             Err(format!("An error occurred: {err}"))
         }
     }
-}
\ No newline at end of file
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MEMBER_ROLE: RoleId = RoleId::new(1);
+    const USER: UserId = UserId::new(2);
+    const OTHER_USER: UserId = UserId::new(3);
+
+    fn channel(permission_overwrites: Vec<PermissionOverwrite>) -> GuildChannel {
+        let mut channel = GuildChannel::default();
+        channel.permission_overwrites = permission_overwrites;
+        channel
+    }
+
+    #[test]
+    fn public_channels_and_viewers_can_see_the_channel() {
+        assert!(is_visible_to(&channel(vec![channel_public!(MEMBER_ROLE)]), MEMBER_ROLE, USER));
+        assert!(is_visible_to(&channel(vec![channel_viewable!(MEMBER_ROLE)]), MEMBER_ROLE, USER));
+
+        let private = channel(vec![hide_to_everyone!(MEMBER_ROLE), user_viewer!(USER)]);
+        assert!(is_visible_to(&private, MEMBER_ROLE, USER));
+        assert!(!is_visible_to(&private, MEMBER_ROLE, OTHER_USER));
+    }
+
+    #[test]
+    fn banned_users_can_not_see_public_channels() {
+        let public = channel(vec![channel_public!(MEMBER_ROLE), user_banned!(USER)]);
+        assert!(!is_visible_to(&public, MEMBER_ROLE, USER));
+        assert!(is_visible_to(&public, MEMBER_ROLE, OTHER_USER));
+    }
+}
@@ -26,7 +26,7 @@ pub async fn run(ctx: &Context, cmd: CommandInteraction) {
                     if profile.is_archive(parent_id) {
                         if let Some(category) = channel.category {
                             let _ = discord_channel.edit(&ctx, EditChannel::new().category(channel.category())).await;
-                            let _ = sort_category(category, &ctx).await;
+                            let _ = sort_category(category, ctx).await;
                             let _ = cmd.edit_response(&ctx, EditInteractionResponse::new()
                                 .content("The channel has been unarchived")).await;
                         } else {
@@ -35,10 +35,10 @@ pub async fn run(ctx: &Context, cmd: CommandInteraction) {
                         }
                     } else {
                         for archive in &profile.archives {
-                            if let Ok(channels) = get_channels_in_category(*archive, &ctx).await {
+                            if let Ok(channels) = get_channels_in_category(*archive, ctx).await {
                                 if channels.len() < 50 {
                                     let _ = discord_channel.edit(&ctx, EditChannel::new().category(Some(ChannelId::new(*archive)))).await;
-                                    let _ = sort_category(*archive, &ctx).await;
+                                    let _ = sort_category(*archive, ctx).await;
                                     let _ = cmd.edit_response(&ctx, EditInteractionResponse::new()
                                         .content("The channel has been archived")).await;
                                     break;
@@ -35,7 +35,7 @@ pub async fn run(ctx: &Context, cmd: CommandInteraction) {
                         if let Some(old_category) = old_category {
                             if discord_channel.parent_id == Some(ChannelId::new(old_category)) {
                                 let _ = discord_channel.edit(&ctx, EditChannel::new().category(Some(ChannelId::new(category.id)))).await;
-                                let _ = sort_category(category.id, &ctx).await;
+                                let _ = sort_category(category.id, ctx).await;
                             }
                         }
                         let _ = cmd.edit_response(&ctx, EditInteractionResponse::new()
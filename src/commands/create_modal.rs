@@ -16,7 +16,7 @@ pub async fn run(ctx: &Context, modal: ModalInteraction) {
                 if let Some(channel_name) = &txt.value {
                     if let ActionRowComponent::InputText(txt) = &modal.data.components[1].components[0] {
                         if let Some(channel_topic) = &txt.value {
-                            match create_channel(modal.user.id, channel_name, channel_topic, cat, &ctx).await {
+                            match create_channel(modal.user.id, channel_name, channel_topic, cat, ctx).await {
                                 Ok(channel) => {
                                     if let Some(member) = &modal.member {
                                         let _ = member.add_role(&ctx, profile.roles.conlanger()).await;
@@ -26,7 +26,7 @@ pub async fn run(ctx: &Context, modal: ModalInteraction) {
                                     .await {
                                     Ok(guild_channel) => {
                                         if let Some(parent_id) = guild_channel.parent_id {
-                                            let _ = sort_category(parent_id.get(), &ctx).await;
+                                            let _ = sort_category(parent_id.get(), ctx).await;
                                         }
                                         let _ = modal.edit_response(&ctx, EditInteractionResponse::new()
                                             .content("Channel edited!")).await;
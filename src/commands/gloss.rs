@@ -0,0 +1,45 @@
+use serenity::all::{CommandInteraction, CommandOptionType, Context, CreateCommand, InputTextStyle};
+use serenity::builder::{CreateActionRow, CreateCommandOption, CreateInputText, CreateInteractionResponse, CreateModal};
+
+pub fn register() -> CreateCommand {
+    CreateCommand::new("gloss")
+        .description("Write an example sentence with aligned interlinear glosses")
+        .add_option(CreateCommandOption::new(CommandOptionType::Boolean, "lookup", "Fill glosses left empty or written as ? from the lexicon of this channel")
+            .required(false))
+        .add_option(CreateCommandOption::new(CommandOptionType::Boolean, "small-caps", "Write abbreviations like NOM in small caps. Defaults to true")
+            .required(false))
+}
+
+pub async fn run(ctx: &Context, cmd: CommandInteraction) {
+    let mut lookup = false;
+    let mut small_caps = true;
+    for option in &cmd.data.options {
+        match option.name.as_str() {
+            "lookup" => lookup = option.value.as_bool().unwrap_or(false),
+            "small-caps" => small_caps = option.value.as_bool().unwrap_or(true),
+            _ => {}
+        }
+    }
+
+    let _ = cmd.create_response(&ctx, CreateInteractionResponse::Modal(
+        CreateModal::new(format!("gloss:{lookup}:{small_caps}"), "Interlinear Gloss")
+            .components(vec![
+                CreateActionRow::InputText(CreateInputText::new(InputTextStyle::Paragraph, "Original sentence", "0")
+                    .required(false)
+                    .max_length(1000)
+                    .placeholder("Kalan tupas.")),
+                CreateActionRow::InputText(CreateInputText::new(InputTextStyle::Paragraph, "Morphemes", "1")
+                    .required(true)
+                    .max_length(1000)
+                    .placeholder("kala-n tupa-s")),
+                CreateActionRow::InputText(CreateInputText::new(InputTextStyle::Paragraph, "Glosses", "2")
+                    .required(!lookup)
+                    .max_length(1000)
+                    .placeholder(if lookup { "?-ACC see-PST" } else { "fish-ACC see-PST" })),
+                CreateActionRow::InputText(CreateInputText::new(InputTextStyle::Paragraph, "Translation", "3")
+                    .required(false)
+                    .max_length(1000)
+                    .placeholder("I saw the fish."))
+            ])
+    )).await;
+}
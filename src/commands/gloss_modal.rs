@@ -0,0 +1,80 @@
+use std::collections::HashMap;
+use crate::database::get_lexicon;
+use crate::interlinear::Interlinear;
+use crate::DatabasePoolKey;
+use serenity::all::{ActionRowComponent, Context, ModalInteraction};
+use serenity::builder::{CreateAttachment, CreateInteractionResponseFollowup, EditInteractionResponse};
+
+pub async fn run(ctx: &Context, modal: ModalInteraction) {
+    let _ = modal.defer(&ctx).await;
+
+    let values: Vec<&str> = modal.data.components.iter()
+        .filter_map(|row| row.components.first())
+        .map(|component| match component {
+            ActionRowComponent::InputText(txt) => txt.value.as_deref().unwrap_or(""),
+            _ => ""
+        })
+        .collect();
+    if values.len() != 4 {
+        return;
+    }
+    let mut settings = modal.data.custom_id.split(':').skip(1);
+    let lookup = settings.next() == Some("true");
+    let small_caps = settings.next() != Some("false");
+
+    let mut interlinear = match Interlinear::parse(values[0], values[1], values[2]) {
+        Ok(interlinear) => interlinear,
+        Err(err) => {
+            let _ = modal.edit_response(&ctx, EditInteractionResponse::new()
+                .content(err)).await;
+            return;
+        }
+    };
+
+    let mut note = String::new();
+    if lookup {
+        let data = ctx.data.read().await;
+        let database_pool = data.get::<DatabasePoolKey>().unwrap();
+        let lexicon = match get_lexicon(database_pool.clone(), modal.channel_id).await {
+            Ok(lexicon) => lexicon,
+            Err(err) => {
+                eprintln!("Failed to read the lexicon of channel {}: {err:?}", modal.channel_id);
+                let _ = modal.delete_response(&ctx).await;
+                let _ = modal.create_followup(&ctx, CreateInteractionResponseFollowup::new()
+                    .content(format!("Failed to read the lexicon: {err}"))
+                    .ephemeral(true)).await;
+                return;
+            }
+        };
+
+        // Affixes can be stored with their hyphens, like -n
+        let mut glosses = HashMap::new();
+        for entry in &lexicon {
+            if let Some(gloss) = entry.glosses().first() {
+                glosses.entry(entry.headword.trim_matches(['-', '=', '~']).to_lowercase())
+                    .or_insert(gloss.split_whitespace().collect::<Vec<_>>().join("."));
+            }
+        }
+        let missing = interlinear.fill(|morpheme| glosses.get(&morpheme.to_lowercase()).cloned());
+        if !missing.is_empty() {
+            note = format!("\nNot in the lexicon: {}", missing.join(", "));
+        }
+    }
+
+    let mut content = format!("```\n{}\n```", interlinear.render(small_caps));
+    let translation = values[3].trim();
+    if !translation.is_empty() {
+        content.push_str(&format!("\n‘{translation}’"));
+    }
+    content.push_str(&note);
+
+    let response = if content.chars().count() <= 2000 {
+        EditInteractionResponse::new()
+            .content(content)
+    } else {
+        let text = content.replace("```\n", "").replace("\n```", "");
+        EditInteractionResponse::new()
+            .new_attachment(CreateAttachment::bytes(text, "gloss.txt"))
+    };
+    let _ = modal.edit_response(&ctx, response).await;
+}
@@ -0,0 +1,117 @@
+use crate::database::{get_paradigm, get_words_by_headword};
+use crate::morphology::Paradigm;
+use crate::DatabasePoolKey;
+use serenity::all::{CommandInteraction, CommandOptionType, Context};
+use serenity::builder::{CreateAttachment, CreateCommand, CreateCommandOption, CreateEmbed, EditInteractionResponse};
+use sqlx::Error;
+
+pub fn register() -> CreateCommand {
+    CreateCommand::new("inflect")
+        .description("Show all forms of a word using a paradigm of this channel")
+        .add_option(CreateCommandOption::new(CommandOptionType::String, "word", "A word from the lexicon or any stem")
+            .max_length(100)
+            .required(true))
+        .add_option(CreateCommandOption::new(CommandOptionType::String, "paradigm", "The paradigm to use. Defaults to the paradigm of the word in the lexicon")
+            .max_length(64)
+            .required(false))
+        .add_option(CreateCommandOption::new(CommandOptionType::Boolean, "file", "Send the table as a Markdown file")
+            .required(false))
+}
+
+pub async fn run(ctx: &Context, cmd: CommandInteraction) {
+    let _ = cmd.defer(&ctx).await;
+
+    let response = match inflect(ctx, &cmd).await {
+        Ok(response) => response,
+        Err(err) => EditInteractionResponse::new()
+            .content(err)
+    };
+    let _ = cmd.edit_response(&ctx, response).await;
+}
+
+async fn inflect(ctx: &Context, cmd: &CommandInteraction) -> Result<EditInteractionResponse, String> {
+    let data = ctx.data.read().await;
+    let database_pool = data.get::<DatabasePoolKey>().unwrap();
+
+    let mut word = "";
+    let mut name = None;
+    let mut file = false;
+    for option in &cmd.data.options {
+        match option.name.as_str() {
+            "word" => word = option.value.as_str().unwrap().trim(),
+            "paradigm" => name = option.value.as_str().map(|name| name.trim().to_string()),
+            "file" => file = option.value.as_bool().unwrap_or(false),
+            _ => {}
+        }
+    }
+
+    if name.is_none() {
+        let entries = get_words_by_headword(database_pool.clone(), cmd.channel_id, word).await
+            .map_err(|err| format!("Failed to look up {word}: {err}"))?;
+        name = entries.into_iter().find_map(|entry| entry.paradigm);
+    }
+    let Some(name) = name else {
+        return Err(format!("{word} has no paradigm in the lexicon. Pick one with the paradigm option"));
+    };
+
+    let saved = match get_paradigm(database_pool.clone(), cmd.channel_id, &name).await {
+        Ok(saved) => saved,
+        Err(Error::RowNotFound) => return Err(format!("Paradigm not found: {name}")),
+        Err(err) => return Err(format!("Failed to load paradigm: {err}"))
+    };
+    let paradigm = Paradigm::parse(&saved.definition)?;
+    let forms = paradigm.inflect(word);
+
+    let title = format!("{word} ({name})");
+    match table_embed(&paradigm, &forms) {
+        Some(embed) if !file => Ok(EditInteractionResponse::new()
+            .embed(embed.title(title))),
+        _ => Ok(EditInteractionResponse::new()
+            .content(title)
+            .new_attachment(CreateAttachment::bytes(to_markdown(&paradigm, &forms), "inflection.md")))
+    }
+}
+
+fn form(form: &Option<String>) -> &str {
+    form.as_deref().unwrap_or("—")
+}
+
+/// Up to two columns fit side by side as inline fields. Wider tables get a field per row instead. Returns `None` if
+/// the table is too large for an embed.
+fn table_embed(paradigm: &Paradigm, forms: &[Vec<Option<String>>]) -> Option<CreateEmbed> {
+    let mut fields = Vec::new();
+    if paradigm.columns.len() == 1 {
+        let description = paradigm.rows.iter().zip(forms)
+            .map(|(row, forms)| format!("**{row}**: {}", form(&forms[0])))
+            .collect::<Vec<_>>()
+            .join("\n");
+        return (description.chars().count() <= 4096).then(|| CreateEmbed::new().description(description));
+    } else if paradigm.columns.len() == 2 {
+        fields.push(("\u{200b}".to_string(), paradigm.rows.iter().map(|row| format!("**{row}**")).collect::<Vec<_>>().join("\n")));
+        for (idx, column) in paradigm.columns.iter().enumerate() {
+            fields.push((column.clone(), forms.iter().map(|forms| form(&forms[idx])).collect::<Vec<_>>().join("\n")));
+        }
+    } else {
+        for (row, forms) in paradigm.rows.iter().zip(forms) {
+            fields.push((row.clone(), paradigm.columns.iter().zip(forms)
+                .map(|(column, forms)| format!("*{column}* {}", form(forms)))
+                .collect::<Vec<_>>()
+                .join("\n")));
+        }
+    }
+
+    let size: usize = fields.iter().map(|(name, value)| name.chars().count() + value.chars().count()).sum();
+    if fields.len() > 25 || size > 5000 || fields.iter().any(|(name, value)| name.chars().count() > 256 || value.chars().count() > 1024) {
+        return None;
+    }
+    Some(fields.into_iter().fold(CreateEmbed::new(), |embed, (name, value)| embed.field(name, value, true)))
+}
+
+fn to_markdown(paradigm: &Paradigm, forms: &[Vec<Option<String>>]) -> String {
+    let mut result = format!("| | {} |\n", paradigm.columns.join(" | "));
+    result.push_str(&format!("|---|{}\n", "---|".repeat(paradigm.columns.len())));
+    for (row, forms) in paradigm.rows.iter().zip(forms) {
+        result.push_str(&format!("| **{row}** | {} |\n", forms.iter().map(form).collect::<Vec<_>>().join(" | ")));
+    }
+    result
+}
@@ -0,0 +1,52 @@
+use serenity::all::{CommandInteraction, CommandOptionType, Context, CreateCommand};
+use serenity::builder::{CreateAttachment, CreateCommandOption, EditInteractionResponse};
+use crate::xsampa::{from_ipa, to_ipa, Notation};
+
+pub fn register() -> CreateCommand {
+    CreateCommand::new("ipa")
+        .description("Convert X-SAMPA to IPA or back")
+        .add_option(CreateCommandOption::new(CommandOptionType::String, "text", "The text to convert. Example: \"t_hEst\"")
+            .max_length(2000)
+            .required(true))
+        .add_option(CreateCommandOption::new(CommandOptionType::String, "notation", "The ASCII notation. Defaults to X-SAMPA")
+            .add_string_choice("X-SAMPA", "x-sampa")
+            .add_string_choice("Conlang X-SAMPA (CXS)", "cxs")
+            .required(false))
+        .add_option(CreateCommandOption::new(CommandOptionType::Boolean, "reverse", "Convert IPA to the ASCII notation instead")
+            .required(false))
+}
+
+pub async fn run(ctx: &Context, cmd: CommandInteraction) {
+    let _ = cmd.defer(&ctx).await;
+
+    let mut text = "";
+    let mut notation = Ok(Notation::XSampa);
+    let mut reverse = false;
+    for option in &cmd.data.options {
+        match option.name.as_str() {
+            "text" => text = option.value.as_str().unwrap(),
+            "notation" => notation = Notation::parse(option.value.as_str().unwrap()),
+            "reverse" => reverse = option.value.as_bool().unwrap_or(false),
+            _ => {}
+        }
+    }
+
+    let response = match notation {
+        Ok(notation) if reverse => code_block(&from_ipa(text, notation), "ipa.txt"),
+        Ok(notation) => code_block(&to_ipa(text, notation), "ipa.txt"),
+        Err(err) => EditInteractionResponse::new()
+            .content(err)
+    };
+    let _ = cmd.edit_response(&ctx, response).await;
+}
+
+/// Shows `text` in a code block, or as an attachment named `file_name` when it is too long or contains ```.
+pub fn code_block(text: &str, file_name: &str) -> EditInteractionResponse {
+    if text.chars().count() <= 1990 && !text.contains("```") {
+        EditInteractionResponse::new()
+            .content(format!("```\n{text}\n```"))
+    } else {
+        EditInteractionResponse::new()
+            .new_attachment(CreateAttachment::bytes(text.to_string(), file_name))
+    }
+}
@@ -0,0 +1,24 @@
+use serenity::all::{CommandInteraction, CommandType, Context, CreateCommand};
+use serenity::builder::EditInteractionResponse;
+use crate::commands::ipa::code_block;
+use crate::xsampa::{to_ipa, Notation};
+
+pub const NAME: &str = "X-SAMPA to IPA";
+
+pub fn register() -> CreateCommand {
+    CreateCommand::new(NAME)
+        .kind(CommandType::Message)
+}
+
+pub async fn run(ctx: &Context, cmd: CommandInteraction) {
+    let _ = cmd.defer_ephemeral(&ctx).await;
+
+    let message = cmd.data.target_id
+        .and_then(|id| cmd.data.resolved.messages.get(&id.to_message_id()));
+    let response = match message {
+        Some(message) if !message.content.trim().is_empty() => code_block(&to_ipa(&message.content, Notation::XSampa), "ipa.txt"),
+        _ => EditInteractionResponse::new()
+            .content("The message has no text to convert")
+    };
+    let _ = cmd.edit_response(&ctx, response).await;
+}
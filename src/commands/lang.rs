@@ -0,0 +1,184 @@
+use crate::commands::get_option;
+use crate::database::{count_words, get_channel_by_id, get_language, save_language};
+use crate::language::Language;
+use crate::profile::Profile;
+use crate::DatabasePoolKey;
+use serenity::all::{CommandDataOption, CommandDataOptionValue, CommandInteraction, CommandOptionType, Context, UserId};
+use serenity::builder::{CreateCommand, CreateCommandOption, CreateEmbed, CreateEmbedAuthor, EditInteractionResponse};
+use sqlx::Error;
+
+const WORD_ORDERS: [&str; 7] = ["SOV", "SVO", "VSO", "VOS", "OVS", "OSV", "Free"];
+const ALIGNMENTS: [&str; 6] = ["Nominative–accusative", "Ergative–absolutive", "Tripartite", "Split ergative", "Active–stative", "Direct–inverse"];
+const MORPHOLOGIES: [&str; 5] = ["Isolating", "Agglutinative", "Fusional", "Polysynthetic", "Oligosynthetic"];
+const STATUSES: [&str; 5] = ["Idea", "In development", "Usable", "Complete", "On hiatus"];
+
+fn choices(option: CreateCommandOption, values: &[&str]) -> CreateCommandOption {
+    values.iter().fold(option, |option, value| option.add_string_choice(*value, *value))
+}
+
+pub fn register(profile: &Profile) -> CreateCommand {
+    let kind = CreateCommandOption::new(CommandOptionType::String, "type", "What kind of language it is")
+        .required(false);
+    let kind = profile.categories.iter().fold(kind, |option, category| option.add_string_choice(&category.name, &category.name));
+
+    CreateCommand::new("lang")
+        .description("Describe the language of this conlang channel")
+        .add_option(CreateCommandOption::new(CommandOptionType::SubCommand, "set", "Set details of the language. Use - to clear a text field")
+            .add_sub_option(CreateCommandOption::new(CommandOptionType::String, "name", "Name of the language in English")
+                .max_length(100)
+                .required(false))
+            .add_sub_option(CreateCommandOption::new(CommandOptionType::String, "autonym", "Name of the language in the language itself")
+                .max_length(100)
+                .required(false))
+            .add_sub_option(kind)
+            .add_sub_option(choices(CreateCommandOption::new(CommandOptionType::String, "word-order", "Basic order of subject, verb and object"), &WORD_ORDERS)
+                .required(false))
+            .add_sub_option(choices(CreateCommandOption::new(CommandOptionType::String, "alignment", "Morphosyntactic alignment"), &ALIGNMENTS)
+                .required(false))
+            .add_sub_option(choices(CreateCommandOption::new(CommandOptionType::String, "morphology", "Morphological type"), &MORPHOLOGIES)
+                .required(false))
+            .add_sub_option(choices(CreateCommandOption::new(CommandOptionType::String, "status", "How far along the language is"), &STATUSES)
+                .required(false))
+            .add_sub_option(CreateCommandOption::new(CommandOptionType::String, "description", "A short description of the language")
+                .max_length(1000)
+                .required(false)))
+        .add_option(CreateCommandOption::new(CommandOptionType::SubCommand, "info", "Show the profile card of the language"))
+}
+
+pub async fn run(ctx: &Context, cmd: CommandInteraction) {
+    let _ = cmd.defer(&ctx).await;
+
+    if let CommandDataOptionValue::SubCommand(options) = &cmd.data.options[0].value {
+        let result = match cmd.data.options[0].name.as_str() {
+            "set" => set(ctx, &cmd, options).await,
+            "info" => info(ctx, &cmd).await,
+            _ => Err("Subcommand not implemented".to_string())
+        };
+
+        if let Err(err) = result {
+            let _ = cmd.edit_response(&ctx, EditInteractionResponse::new()
+                .content(err)).await;
+        }
+    }
+}
+
+/// `Some(None)` if the field should be cleared with `-`, `None` if the option was not given.
+fn get_text(options: &[CommandDataOption], name: &str) -> Option<Option<String>> {
+    let value = get_option(options, name)?.as_str()?.trim();
+    match value {
+        "-" | "" => Some(None),
+        value => Some(Some(value.to_string()))
+    }
+}
+
+async fn set(ctx: &Context, cmd: &CommandInteraction, options: &[CommandDataOption]) -> Result<(), String> {
+    let data = ctx.data.read().await;
+    let database_pool = data.get::<DatabasePoolKey>().unwrap();
+    let profile = data.get::<Profile>().unwrap();
+
+    let channel = get_channel_by_id(database_pool.clone(), cmd.channel_id).await
+        .map_err(|_| "Language profiles are only available in conlang channels".to_string())?;
+    if !channel.check_contributor(ctx, &cmd.user, &cmd.member).await {
+        return Err("Only the channel owner and contributors can change the language profile".to_string());
+    }
+
+    let mut language = match get_language(database_pool.clone(), channel.id).await {
+        Ok(language) => language,
+        Err(Error::RowNotFound) => Language {
+            channel: channel.id,
+            name: String::new(),
+            autonym: None,
+            kind: channel.category
+                .and_then(|id| profile.categories.iter().find(|category| category.id == id))
+                .map(|category| category.name.clone()),
+            word_order: None,
+            alignment: None,
+            morphology: None,
+            status: None,
+            description: None,
+            updated: 0
+        },
+        Err(err) => return Err(format!("Failed to load the language profile: {err}"))
+    };
+
+    if let Some(name) = get_text(options, "name") {
+        language.name = name.ok_or("The name of the language can not be cleared")?;
+    }
+    let fields = [
+        ("autonym", &mut language.autonym),
+        ("type", &mut language.kind),
+        ("word-order", &mut language.word_order),
+        ("alignment", &mut language.alignment),
+        ("morphology", &mut language.morphology),
+        ("status", &mut language.status),
+        ("description", &mut language.description)
+    ];
+    for (name, field) in fields {
+        if let Some(value) = get_text(options, name) {
+            *field = value;
+        }
+    }
+    if language.name.is_empty() {
+        return Err("Give the language a name first. Example: /lang set name:Toki Pona".to_string());
+    }
+
+    let name = language.name.clone();
+    save_language(database_pool.clone(), language).await
+        .map_err(|err| format!("Failed to save the language profile: {err}"))?;
+
+    let _ = cmd.edit_response(&ctx, EditInteractionResponse::new()
+        .content(format!("Language profile of {name} saved. Show it with /lang info"))).await;
+    Ok(())
+}
+
+async fn info(ctx: &Context, cmd: &CommandInteraction) -> Result<(), String> {
+    let data = ctx.data.read().await;
+    let database_pool = data.get::<DatabasePoolKey>().unwrap();
+
+    let channel = get_channel_by_id(database_pool.clone(), cmd.channel_id).await
+        .map_err(|_| "Language profiles are only available in conlang channels".to_string())?;
+    let language = match get_language(database_pool.clone(), channel.id).await {
+        Ok(language) => language,
+        Err(Error::RowNotFound) => return Err("This channel has no language profile. Create one with /lang set".to_string()),
+        Err(err) => return Err(format!("Failed to load the language profile: {err}"))
+    };
+    let words = count_words(database_pool.clone(), channel.id).await
+        .map_err(|err| format!("Failed to count words: {err}"))?;
+
+    let mut embed = card(&language, channel.owner, words);
+    if let Ok(owner) = channel.owner.to_user(&ctx).await {
+        embed = embed.author(CreateEmbedAuthor::new(owner.display_name()).icon_url(owner.face()));
+    }
+    let _ = cmd.edit_response(&ctx, EditInteractionResponse::new()
+        .embed(embed)).await;
+    Ok(())
+}
+
+fn card(language: &Language, owner: UserId, words: u64) -> CreateEmbed {
+    let title = match &language.autonym {
+        Some(autonym) => format!("{} ({autonym})", language.name),
+        None => language.name.clone()
+    };
+
+    let mut description = String::new();
+    if let Some(text) = &language.description {
+        description.push_str(text);
+        description.push_str("\n\n");
+    }
+    description.push_str(&format!("Updated <t:{}:R>", language.updated));
+
+    let fields = [
+        ("Type", &language.kind),
+        ("Word order", &language.word_order),
+        ("Alignment", &language.alignment),
+        ("Morphology", &language.morphology),
+        ("Status", &language.status)
+    ];
+    CreateEmbed::new()
+        .title(title)
+        .description(description)
+        .fields(fields.into_iter()
+            .filter_map(|(name, value)| value.as_ref().map(|value| (name, value.clone(), true))))
+        .field("Lexicon", format!("{words} words"), true)
+        .field("Owner", format!("<@{owner}>"), true)
+}
@@ -1,4 +1,4 @@
-use serenity::all::UserId;
+use serenity::all::{CommandDataOption, CommandDataOptionValue, UserId};
 
 pub mod archive;
 pub mod ban;
@@ -13,10 +13,35 @@ pub mod delete_interaction;
 pub mod edit;
 pub mod edit_modal;
 pub mod fixperms;
+pub mod gloss;
+pub mod gloss_modal;
+pub mod inflect;
+pub mod ipa;
+pub mod ipa_message;
+pub mod lang;
 pub mod migrate;
 pub mod mode;
+pub mod paradigm;
+pub mod phonology;
+pub mod romanize;
+pub mod search;
+pub mod search_interaction;
+pub mod soundchange;
 pub mod unban;
 pub mod viewer;
+pub mod word;
+pub mod word_export;
+pub mod word_import;
+pub mod word_interaction;
 pub mod wordgen;
+pub mod wordgen_editor;
+pub mod wordgen_import;
+pub mod wordgen_modal;
+pub mod wordgen_preset;
 
-pub const DEVELOPER: UserId = UserId::new(796368453152800778);
\ No newline at end of file
+pub const DEVELOPER: UserId = UserId::new(796368453152800778);
+
+/// The value of the option called `name`, usually one of a subcommand's options.
+pub fn get_option<'a>(options: &'a [CommandDataOption], name: &str) -> Option<&'a CommandDataOptionValue> {
+    options.iter().find(|option| option.name == name).map(|option| &option.value)
+}
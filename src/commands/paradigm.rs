@@ -0,0 +1,165 @@
+use crate::commands::get_option;
+use crate::database::{delete_paradigm, get_channel_by_id, get_paradigm, get_paradigms, save_paradigm};
+use crate::morphology::{Paradigm, SavedParadigm};
+use crate::DatabasePoolKey;
+use serenity::all::{CommandDataOption, CommandDataOptionValue, CommandInteraction, CommandOptionType, Context};
+use serenity::builder::{CreateAttachment, CreateCommand, CreateCommandOption, CreateEmbed, EditInteractionResponse};
+use sqlx::Error;
+
+const MAX_DEFINITION_SIZE: usize = 20_000;
+
+pub fn register() -> CreateCommand {
+    CreateCommand::new("paradigm")
+        .description("Manage the inflection tables of this conlang channel")
+        .add_option(CreateCommandOption::new(CommandOptionType::SubCommand, "set", "Create or replace a paradigm")
+            .add_sub_option(name_option())
+            .add_sub_option(CreateCommandOption::new(CommandOptionType::String, "rules", "Rules separated by ;. Example: rows = sg, pl; pl = *a: -a +i | +i")
+                .max_length(4000)
+                .required(false))
+            .add_sub_option(CreateCommandOption::new(CommandOptionType::Attachment, "file", "A text file with one rule per line")
+                .required(false)))
+        .add_option(CreateCommandOption::new(CommandOptionType::SubCommand, "show", "Show the rules of a paradigm")
+            .add_sub_option(name_option()))
+        .add_option(CreateCommandOption::new(CommandOptionType::SubCommand, "list", "List the paradigms of this channel"))
+        .add_option(CreateCommandOption::new(CommandOptionType::SubCommand, "delete", "Delete a paradigm")
+            .add_sub_option(name_option()))
+}
+
+fn name_option() -> CreateCommandOption {
+    CreateCommandOption::new(CommandOptionType::String, "name", "The name of the paradigm, like noun-1 or a-stems")
+        .max_length(64)
+        .required(true)
+}
+
+pub async fn run(ctx: &Context, cmd: CommandInteraction) {
+    let _ = cmd.defer(&ctx).await;
+
+    if let CommandDataOptionValue::SubCommand(options) = &cmd.data.options[0].value {
+        let result = match cmd.data.options[0].name.as_str() {
+            "set" => set(ctx, &cmd, options).await,
+            "show" => show(ctx, &cmd, options).await,
+            "list" => list(ctx, &cmd).await,
+            "delete" => delete(ctx, &cmd, options).await,
+            _ => Err("Subcommand not implemented".to_string())
+        };
+
+        if let Err(err) = result {
+            let _ = cmd.edit_response(&ctx, EditInteractionResponse::new()
+                .content(err)).await;
+        }
+    }
+}
+
+async fn set(ctx: &Context, cmd: &CommandInteraction, options: &[CommandDataOption]) -> Result<(), String> {
+    let data = ctx.data.read().await;
+    let database_pool = data.get::<DatabasePoolKey>().unwrap();
+
+    let channel = get_channel_by_id(database_pool.clone(), cmd.channel_id).await
+        .map_err(|_| "Paradigms are only available in conlang channels".to_string())?;
+    if !channel.check_contributor(ctx, &cmd.user, &cmd.member).await {
+        return Err("Only the channel owner and contributors can change paradigms".to_string());
+    }
+
+    let name = get_option(options, "name").and_then(|value| value.as_str()).unwrap().trim();
+    let mut definition = get_option(options, "rules").and_then(|value| value.as_str()).unwrap_or_default().to_string();
+    let attachment = get_option(options, "file")
+        .and_then(|value| value.as_attachment_id())
+        .and_then(|id| cmd.data.resolved.attachments.get(&id));
+    if let Some(attachment) = attachment {
+        if attachment.size as usize > MAX_DEFINITION_SIZE {
+            return Err("The file is too large".to_string());
+        }
+        match attachment.download().await.map(String::from_utf8) {
+            Ok(Ok(content)) => {
+                definition.push('\n');
+                definition.push_str(&content);
+            }
+            _ => return Err("Failed to read the file. Make sure it is a UTF-8 text file".to_string())
+        }
+    }
+    if definition.trim().is_empty() {
+        return Err("Please provide the rules or a file".to_string());
+    }
+
+    let paradigm = Paradigm::parse(&definition)?;
+    save_paradigm(database_pool.clone(), SavedParadigm {
+        channel: channel.id,
+        name: name.to_string(),
+        definition: definition.trim().to_string()
+    }).await.map_err(|err| format!("Failed to save paradigm: {err}"))?;
+
+    let _ = cmd.edit_response(&ctx, EditInteractionResponse::new()
+        .content(format!("Paradigm saved: {name} ({} × {} forms)", paradigm.rows.len(), paradigm.columns.len()))).await;
+    Ok(())
+}
+
+async fn show(ctx: &Context, cmd: &CommandInteraction, options: &[CommandDataOption]) -> Result<(), String> {
+    let data = ctx.data.read().await;
+    let database_pool = data.get::<DatabasePoolKey>().unwrap();
+
+    let name = get_option(options, "name").and_then(|value| value.as_str()).unwrap().trim();
+    let paradigm = match get_paradigm(database_pool.clone(), cmd.channel_id, name).await {
+        Ok(paradigm) => paradigm,
+        Err(Error::RowNotFound) => return Err(format!("Paradigm not found: {name}")),
+        Err(err) => return Err(format!("Failed to load paradigm: {err}"))
+    };
+
+    let rules = paradigm.definition.split(['\n', ';'])
+        .map(str::trim)
+        .filter(|rule| !rule.is_empty())
+        .collect::<Vec<_>>()
+        .join("\n");
+    let response = if rules.chars().count() <= 4000 {
+        EditInteractionResponse::new()
+            .embed(CreateEmbed::new()
+                .title(format!("Paradigm: {}", paradigm.name))
+                .description(format!("```\n{rules}\n```")))
+    } else {
+        EditInteractionResponse::new()
+            .content(format!("Paradigm: {}", paradigm.name))
+            .new_attachment(CreateAttachment::bytes(rules, format!("{}.txt", paradigm.name)))
+    };
+    let _ = cmd.edit_response(&ctx, response).await;
+    Ok(())
+}
+
+async fn list(ctx: &Context, cmd: &CommandInteraction) -> Result<(), String> {
+    let data = ctx.data.read().await;
+    let database_pool = data.get::<DatabasePoolKey>().unwrap();
+
+    let paradigms = get_paradigms(database_pool.clone(), cmd.channel_id).await
+        .map_err(|err| format!("Failed to list paradigms: {err}"))?;
+    let list = if paradigms.is_empty() {
+        "None. Create one with /paradigm set".to_string()
+    } else {
+        paradigms.into_iter().map(|paradigm| paradigm.name).collect::<Vec<_>>().join(", ")
+    };
+
+    let _ = cmd.edit_response(&ctx, EditInteractionResponse::new()
+        .add_embed(CreateEmbed::new()
+            .title("Paradigms")
+            .description(list))).await;
+    Ok(())
+}
+
+async fn delete(ctx: &Context, cmd: &CommandInteraction, options: &[CommandDataOption]) -> Result<(), String> {
+    let data = ctx.data.read().await;
+    let database_pool = data.get::<DatabasePoolKey>().unwrap();
+
+    let channel = get_channel_by_id(database_pool.clone(), cmd.channel_id).await
+        .map_err(|_| "Paradigms are only available in conlang channels".to_string())?;
+    if !channel.check_contributor(ctx, &cmd.user, &cmd.member).await {
+        return Err("Only the channel owner and contributors can change paradigms".to_string());
+    }
+
+    let name = get_option(options, "name").and_then(|value| value.as_str()).unwrap().trim();
+    match delete_paradigm(database_pool.clone(), channel.id, name).await {
+        Ok(true) => {
+            let _ = cmd.edit_response(&ctx, EditInteractionResponse::new()
+                .content(format!("Paradigm deleted: {name}"))).await;
+            Ok(())
+        }
+        Ok(false) => Err(format!("Paradigm not found: {name}")),
+        Err(err) => Err(format!("Failed to delete paradigm: {err}"))
+    }
+}
@@ -0,0 +1,123 @@
+use crate::commands::get_option;
+use crate::database::{delete_inventory, get_channel_by_id, get_inventory, save_inventory};
+use crate::phonology::{Inventory, MISSING_INVENTORY};
+use crate::DatabasePoolKey;
+use serenity::all::{CommandDataOption, CommandDataOptionValue, CommandInteraction, CommandOptionType, Context};
+use serenity::builder::{CreateAttachment, CreateCommand, CreateCommandOption, CreateEmbed, CreateEmbedFooter, EditInteractionResponse};
+use sqlx::Error;
+
+pub fn register() -> CreateCommand {
+    CreateCommand::new("phonology")
+        .description("Manage the phoneme inventory of this conlang channel")
+        .add_option(CreateCommandOption::new(CommandOptionType::SubCommand, "set", "Set the consonants and vowels by IPA symbol")
+            .add_sub_option(CreateCommandOption::new(CommandOptionType::String, "consonants", "Consonants separated by spaces. Example: p t k tʃ m n s h")
+                .max_length(1000)
+                .required(false))
+            .add_sub_option(CreateCommandOption::new(CommandOptionType::String, "vowels", "Vowels separated by spaces. Example: a e i o u aː")
+                .max_length(500)
+                .required(false)))
+        .add_option(CreateCommandOption::new(CommandOptionType::SubCommand, "show", "Show the consonant and vowel charts"))
+        .add_option(CreateCommandOption::new(CommandOptionType::SubCommand, "delete", "Delete the phoneme inventory"))
+}
+
+pub async fn run(ctx: &Context, cmd: CommandInteraction) {
+    let _ = cmd.defer(&ctx).await;
+
+    if let CommandDataOptionValue::SubCommand(options) = &cmd.data.options[0].value {
+        let result = match cmd.data.options[0].name.as_str() {
+            "set" => set(ctx, &cmd, options).await,
+            "show" => show(ctx, &cmd).await,
+            "delete" => delete(ctx, &cmd).await,
+            _ => Err("Subcommand not implemented".to_string())
+        };
+
+        if let Err(err) = result {
+            let _ = cmd.edit_response(&ctx, EditInteractionResponse::new()
+                .content(err)).await;
+        }
+    }
+}
+
+async fn set(ctx: &Context, cmd: &CommandInteraction, options: &[CommandDataOption]) -> Result<(), String> {
+    let data = ctx.data.read().await;
+    let database_pool = data.get::<DatabasePoolKey>().unwrap();
+
+    let channel = get_channel_by_id(database_pool.clone(), cmd.channel_id).await
+        .map_err(|_| "Phoneme inventories are only available in conlang channels".to_string())?;
+    if !channel.check_contributor(ctx, &cmd.user, &cmd.member).await {
+        return Err("Only the channel owner and contributors can change the phoneme inventory".to_string());
+    }
+
+    let consonants = get_option(options, "consonants").and_then(|value| value.as_str()).unwrap_or_default();
+    let vowels = get_option(options, "vowels").and_then(|value| value.as_str()).unwrap_or_default();
+    let inventory = Inventory::parse(channel.id, consonants, vowels)?;
+    let summary = format!("Phoneme inventory saved: {} consonants and {} vowels", inventory.consonants.len(), inventory.vowels.len());
+    save_inventory(database_pool.clone(), inventory).await
+        .map_err(|err| format!("Failed to save the phoneme inventory: {err}"))?;
+
+    let _ = cmd.edit_response(&ctx, EditInteractionResponse::new()
+        .content(summary)).await;
+    Ok(())
+}
+
+async fn show(ctx: &Context, cmd: &CommandInteraction) -> Result<(), String> {
+    let data = ctx.data.read().await;
+    let database_pool = data.get::<DatabasePoolKey>().unwrap();
+
+    let inventory = match get_inventory(database_pool.clone(), cmd.channel_id).await {
+        Ok(inventory) => inventory,
+        Err(Error::RowNotFound) => return Err(MISSING_INVENTORY.to_string()),
+        Err(err) => return Err(format!("Failed to load the phoneme inventory: {err}"))
+    };
+
+    let mut charts = Vec::new();
+    if !inventory.consonants.is_empty() {
+        charts.push(("Consonants", inventory.consonant_chart()));
+    }
+    if !inventory.vowels.is_empty() {
+        charts.push(("Vowels", inventory.vowel_chart()));
+    }
+
+    let description = charts.iter()
+        .map(|(title, chart)| format!("**{title}**\n```\n{chart}\n```"))
+        .collect::<Vec<_>>()
+        .join("\n");
+    let response = if description.chars().count() <= 4096 {
+        EditInteractionResponse::new()
+            .embed(CreateEmbed::new()
+                .title("Phonology")
+                .description(description)
+                .footer(CreateEmbedFooter::new("Use classes like @stops or @front-vowels in wordgen categories")))
+    } else {
+        let file = charts.iter()
+            .map(|(title, chart)| format!("{title}\n\n{chart}\n"))
+            .collect::<Vec<_>>()
+            .join("\n");
+        EditInteractionResponse::new()
+            .content("Phonology")
+            .new_attachment(CreateAttachment::bytes(file, "phonology.txt"))
+    };
+    let _ = cmd.edit_response(&ctx, response).await;
+    Ok(())
+}
+
+async fn delete(ctx: &Context, cmd: &CommandInteraction) -> Result<(), String> {
+    let data = ctx.data.read().await;
+    let database_pool = data.get::<DatabasePoolKey>().unwrap();
+
+    let channel = get_channel_by_id(database_pool.clone(), cmd.channel_id).await
+        .map_err(|_| "Phoneme inventories are only available in conlang channels".to_string())?;
+    if !channel.check_contributor(ctx, &cmd.user, &cmd.member).await {
+        return Err("Only the channel owner and contributors can change the phoneme inventory".to_string());
+    }
+
+    match delete_inventory(database_pool.clone(), channel.id).await {
+        Ok(true) => {
+            let _ = cmd.edit_response(&ctx, EditInteractionResponse::new()
+                .content("Phoneme inventory deleted")).await;
+            Ok(())
+        }
+        Ok(false) => Err("This channel has no phoneme inventory".to_string()),
+        Err(err) => Err(format!("Failed to delete the phoneme inventory: {err}"))
+    }
+}
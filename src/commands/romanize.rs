@@ -0,0 +1,72 @@
+use serenity::all::{CommandInteraction, CommandOptionType, Context, CreateCommand};
+use serenity::builder::{CreateCommandOption, EditInteractionResponse};
+use crate::commands::ipa::code_block;
+use crate::commands::wordgen::read_text;
+use crate::orthography::Orthography;
+
+pub fn register() -> CreateCommand {
+    CreateCommand::new("romanize")
+        .description("Convert text between phonemic and written forms")
+        .add_option(CreateCommandOption::new(CommandOptionType::String, "rules", "Orthography rules separated by ;. Example: ʃ>sh; tʃ>ch; j>y")
+            .max_length(2000)
+            .required(true))
+        .add_option(CreateCommandOption::new(CommandOptionType::String, "text", "The text to convert")
+            .max_length(2000)
+            .required(false))
+        .add_option(CreateCommandOption::new(CommandOptionType::Attachment, "file", "A text file to convert")
+            .required(false))
+        .add_option(CreateCommandOption::new(CommandOptionType::Boolean, "reverse", "Apply the rules from right to left, turning written forms back into phonemes")
+            .required(false))
+}
+
+pub async fn run(ctx: &Context, cmd: CommandInteraction) {
+    let _ = cmd.defer(&ctx).await;
+
+    let mut rules = "";
+    let mut text = String::new();
+    let mut reverse = false;
+    for option in &cmd.data.options {
+        match option.name.as_str() {
+            "rules" => rules = option.value.as_str().unwrap(),
+            "text" => {
+                text.push_str(option.value.as_str().unwrap());
+                text.push('\n');
+            }
+            "reverse" => reverse = option.value.as_bool().unwrap_or(false),
+            "file" => {
+                let attachment = option.value.as_attachment_id()
+                    .and_then(|id| cmd.data.resolved.attachments.get(&id));
+                if let Some(attachment) = attachment {
+                    match read_text(attachment, "file").await {
+                        Ok(content) => text.push_str(&content),
+                        Err(err) => {
+                            let _ = cmd.edit_response(&ctx, EditInteractionResponse::new()
+                                .content(err)).await;
+                            return;
+                        }
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+
+    let response = match romanize(rules, text.trim_end(), reverse) {
+        Ok(result) => code_block(&result, "romanized.txt"),
+        Err(err) => EditInteractionResponse::new()
+            .content(err)
+    };
+    let _ = cmd.edit_response(&ctx, response).await;
+}
+
+fn romanize(rules: &str, text: &str, reverse: bool) -> Result<String, String> {
+    if text.is_empty() {
+        return Err("No text provided. Use the text or file option".to_string());
+    }
+
+    let mut orthography = Orthography::parse(rules)?;
+    if reverse {
+        orthography = orthography.reversed()?;
+    }
+    Ok(orthography.apply(text))
+}
@@ -0,0 +1,114 @@
+use std::sync::Arc;
+use crate::channel::is_visible_to;
+use crate::database::{search_glosses, search_headwords, SqlPool};
+use crate::lexicon::{min_headword_length, LexiconEntry};
+use crate::profile::Profile;
+use crate::DatabasePoolKey;
+use serenity::all::{ButtonStyle, ChannelId, CommandDataOptionValue, CommandInteraction, CommandOptionType, Context, UserId};
+use serenity::builder::{CreateActionRow, CreateButton, CreateCommand, CreateCommandOption, CreateEmbed, CreateEmbedFooter, EditInteractionResponse};
+
+const PAGE_SIZE: usize = 10;
+
+pub fn register() -> CreateCommand {
+    CreateCommand::new("search")
+        .description("Search the lexicons of all channels you can see")
+        .add_option(CreateCommandOption::new(CommandOptionType::SubCommand, "gloss", "Find how every language says something")
+            .add_sub_option(CreateCommandOption::new(CommandOptionType::String, "meaning", "Example: water")
+                .max_length(80)
+                .required(true)))
+        .add_option(CreateCommandOption::new(CommandOptionType::SubCommand, "word", "Find a word by spelling, ignoring accents and small typos")
+            .add_sub_option(CreateCommandOption::new(CommandOptionType::String, "word", "The word as written in the conlang")
+                .max_length(80)
+                .required(true)))
+}
+
+pub async fn run(ctx: &Context, cmd: CommandInteraction) {
+    let _ = cmd.defer_ephemeral(&ctx).await;
+
+    if let CommandDataOptionValue::SubCommand(options) = &cmd.data.options[0].value {
+        let mode = cmd.data.options[0].name.as_str();
+        let query = options.first().and_then(|option| option.value.as_str()).unwrap_or_default().trim();
+
+        let response = match search_page(ctx, cmd.user.id, mode, query, 0).await {
+            Ok((embed, buttons)) => EditInteractionResponse::new()
+                .embed(embed)
+                .components(buttons),
+            Err(err) => EditInteractionResponse::new()
+                .content(err)
+        };
+        let _ = cmd.edit_response(&ctx, response).await;
+    }
+}
+
+async fn search(ctx: &Context, pool: Arc<SqlPool>, user: UserId, mode: &str, query: &str) -> Result<Vec<LexiconEntry>, String> {
+    let data = ctx.data.read().await;
+    let profile = data.get::<Profile>().unwrap();
+    let channels = profile.guild().channels(&ctx).await
+        .map_err(|err| format!("Failed to retrieve channels: {err}"))?;
+    let visible: Vec<ChannelId> = channels.values()
+        .filter(|channel| is_visible_to(channel, profile.roles.member(), user))
+        .map(|channel| channel.id)
+        .collect();
+
+    let entries = match mode {
+        "gloss" => {
+            let longest = query.split(|char: char| !char.is_alphanumeric())
+                .max_by_key(|word| word.len())
+                .unwrap_or_default();
+            search_glosses(pool, &visible, longest).await
+        }
+        _ => search_headwords(pool, &visible, min_headword_length(query)).await
+    }.map_err(|err| format!("Failed to search: {err}"))?;
+
+    let mut results: Vec<(usize, String, LexiconEntry)> = entries.into_iter()
+        .filter_map(|entry| {
+            let rank = match mode {
+                "gloss" => entry.gloss_rank(query),
+                _ => entry.headword_rank(query)
+            }?;
+            let name = channels[&entry.channel].name.clone();
+            Some((rank, name, entry))
+        })
+        .collect();
+    results.sort_by(|(rank_a, name_a, a), (rank_b, name_b, b)| rank_a.cmp(rank_b)
+        .then_with(|| name_a.cmp(name_b))
+        .then_with(|| a.headword.cmp(&b.headword)));
+    Ok(results.into_iter().map(|(_, _, entry)| entry).collect())
+}
+
+/// A page of search results with buttons to the previous and next page. `page` starts at 0.
+pub async fn search_page(ctx: &Context, user: UserId, mode: &str, query: &str, page: usize) -> Result<(CreateEmbed, Vec<CreateActionRow>), String> {
+    let pool = ctx.data.read().await.get::<DatabasePoolKey>().unwrap().clone();
+    let results = search(ctx, pool, user, mode, query).await?;
+    if results.is_empty() {
+        return Err(format!("Nothing found for {query}"));
+    }
+
+    let pages = results.len().div_ceil(PAGE_SIZE);
+    let page = page.min(pages - 1);
+    let title = match mode {
+        "gloss" => format!("Words for \"{query}\""),
+        _ => format!("Words like {query}")
+    };
+    let embed = CreateEmbed::new()
+        .title(title)
+        .description(results.iter().skip(page * PAGE_SIZE).take(PAGE_SIZE).map(|entry| {
+            let summary = format!("<#{}> {}", entry.channel, entry.summary());
+            match summary.char_indices().nth(300) {
+                Some((end, _)) => format!("{}…", &summary[..end]),
+                None => summary
+            }
+        }).collect::<Vec<_>>().join("\n"))
+        .footer(CreateEmbedFooter::new(format!("{} results, page {} of {pages}", results.len(), page + 1)));
+    let buttons = CreateActionRow::Buttons(vec![
+        CreateButton::new(format!("search:{mode}:{}:{query}", page.saturating_sub(1)))
+            .label("Previous")
+            .style(ButtonStyle::Secondary)
+            .disabled(page == 0),
+        CreateButton::new(format!("search:{mode}:{}:{query}", page + 1))
+            .label("Next")
+            .style(ButtonStyle::Secondary)
+            .disabled(page + 1 >= pages)
+    ]);
+    Ok((embed, vec![buttons]))
+}
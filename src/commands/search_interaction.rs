@@ -0,0 +1,25 @@
+use crate::commands::search::search_page;
+use serenity::all::{ComponentInteraction, Context};
+use serenity::builder::{CreateInteractionResponseFollowup, EditInteractionResponse};
+
+pub async fn run(ctx: &Context, comp: ComponentInteraction) {
+    let _ = comp.defer(&ctx).await;
+
+    let mut parts = comp.data.custom_id.splitn(4, ':').skip(1);
+    let mode = parts.next().unwrap_or_default();
+    let page = parts.next().and_then(|page| page.parse::<usize>().ok()).unwrap_or(0);
+    let query = parts.next().unwrap_or_default();
+
+    match search_page(ctx, comp.user.id, mode, query, page).await {
+        Ok((embed, buttons)) => {
+            let _ = comp.edit_response(&ctx, EditInteractionResponse::new()
+                .embed(embed)
+                .components(buttons)).await;
+        }
+        Err(err) => {
+            let _ = comp.create_followup(&ctx, CreateInteractionResponseFollowup::new()
+                .content(err)
+                .ephemeral(true)).await;
+        }
+    }
+}
@@ -0,0 +1,442 @@
+use std::collections::HashMap;
+use serenity::all::{CommandInteraction, CommandOptionType, Context, CreateCommand};
+use serenity::builder::{CreateAttachment, CreateCommandOption, EditInteractionResponse};
+use crate::commands::wordgen::{load_inventory, read_text};
+use crate::features::{find_classes, NaturalClass};
+use crate::phonemes::{category_at, parse_category_option, Categories, PhonemeCategory};
+use crate::phonology::Inventory;
+
+pub fn register() -> CreateCommand {
+    let mut cmd = CreateCommand::new("soundchange")
+        .description("Apply sound changes to a list of words")
+        .add_option(CreateCommandOption::new(CommandOptionType::String, "rules", "Sound changes separated by ;. Example: k>tʃ/_i; V>∅/_#; [-son]>[-voice]/_#")
+            .max_length(2000)
+            .required(true))
+        .add_option(CreateCommandOption::new(CommandOptionType::String, "words", "Words separated by spaces")
+            .max_length(2000)
+            .required(false))
+        .add_option(CreateCommandOption::new(CommandOptionType::Attachment, "wordlist", "A text file with words separated by spaces or lines, e.g. from /wordgen")
+            .required(false));
+
+    for i in 1..=10 {
+        cmd = cmd.add_option(CreateCommandOption::new(CommandOptionType::String, format!("category-{}", i), "Sound categories separated by ;. Example: V:a,e,i,o,u;C:p,t,k")
+            .min_length(3)
+            .max_length(500)
+            .required(false));
+    }
+
+    cmd
+}
+
+pub async fn run(ctx: &Context, cmd: CommandInteraction) {
+    let _ = cmd.defer(&ctx).await;
+
+    let mut words = String::new();
+    for option in &cmd.data.options {
+        if option.name == "words" {
+            words.push_str(option.value.as_str().unwrap());
+            words.push('\n');
+        } else if option.name == "wordlist" {
+            let attachment = option.value.as_attachment_id()
+                .and_then(|id| cmd.data.resolved.attachments.get(&id));
+            if let Some(attachment) = attachment {
+                match read_text(attachment, "word list").await {
+                    Ok(content) => words.push_str(&content),
+                    Err(err) => {
+                        let _ = cmd.edit_response(&ctx, EditInteractionResponse::new()
+                            .content(err)).await;
+                        return;
+                    }
+                }
+            }
+        }
+    }
+
+    let uses_inventory = cmd.data.options.iter()
+        .any(|option| option.value.as_str().is_some_and(|value| value.contains("@[")));
+    let result = match load_inventory(ctx, cmd.channel_id, uses_inventory).await {
+        Ok(inventory) => soundchange(&cmd, &words, inventory.as_ref()),
+        Err(err) => Err(err)
+    };
+
+    match result {
+        Ok(result) => {
+            let _ = cmd.edit_response(&ctx, EditInteractionResponse::new()
+                .new_attachment(CreateAttachment::bytes(result.as_bytes(), "soundchanges.txt"))).await;
+        }
+        Err(err) => {
+            let _ = cmd.edit_response(&ctx, EditInteractionResponse::new()
+                .content(err)).await;
+        }
+    }
+}
+
+fn soundchange(cmd: &CommandInteraction, words: &str, inventory: Option<&Inventory>) -> Result<String, String> {
+    let mut rules = "";
+    let mut categories = HashMap::new();
+    for option in &cmd.data.options {
+        if option.name == "rules" {
+            rules = option.value.as_str().unwrap();
+        } else if let Some(i) = option.name.strip_prefix("category-") {
+            parse_category_option(i, option.value.as_str().unwrap(), &mut categories)?;
+        }
+    }
+
+    let rules = parse_rules(rules, &mut categories, inventory)?;
+    let words: Vec<&str> = words.split_whitespace().collect();
+    if words.is_empty() {
+        return Err("No words provided. Use the words or wordlist option".to_string());
+    }
+
+    let changed: Vec<String> = words.iter()
+        .map(|word| rules.iter().fold(word.to_string(), |word, rule| rule.apply(&word, &categories)))
+        .collect();
+
+    let longest_word = words.iter().map(|word| word.chars().count()).max().unwrap_or(0) + 2;
+
+    let mut result = String::new();
+    for (before, after) in words.into_iter().zip(changed) {
+        result.push_str(before);
+        result.push_str(" ".repeat(longest_word - before.chars().count()).as_str());
+        result.push_str("→  ");
+        result.push_str(after.as_str());
+        result.push('\n');
+    }
+    Ok(result)
+}
+
+/// Parses the rules separated by `;`, adding a category for every feature expression like `[-voice]` they use.
+fn parse_rules<'a>(rules: &str, categories: &mut Categories<'a>, inventory: Option<&'a Inventory>) -> Result<Vec<Rule>, String> {
+    for category in categories.values_mut() {
+        if let [letter] = category.letters[..] {
+            if let Some(class) = NaturalClass::parse(letter.trim())? {
+                *category = natural_class(&class, letter.trim(), inventory)?;
+            }
+        }
+    }
+    for (start, end, class) in find_classes(rules)? {
+        if !categories.contains_key(&rules[start..end]) {
+            categories.insert(rules[start..end].to_string(), natural_class(&class, &rules[start..end], inventory)?);
+        }
+    }
+
+    rules.split(";")
+        .map(str::trim)
+        .filter(|rule| !rule.is_empty())
+        .map(|rule| parse_rule(rule, categories, inventory))
+        .collect()
+}
+
+/// A category of the sounds in a feature expression like `[-sonorant]`, longest first so that `tʰ` is tried before `t`.
+fn natural_class<'a>(class: &NaturalClass, expression: &str, inventory: Option<&'a Inventory>) -> Result<PhonemeCategory<'a>, String> {
+    let mut members = class.members(inventory)?;
+    members.sort_by_key(|member| std::cmp::Reverse(member.len()));
+    PhonemeCategory::uniform(members).ok_or(format!("{expression} matches no sounds"))
+}
+
+enum Token {
+    WordStart,
+    WordEnd,
+    Letter(char),
+    Category(String),
+    /// A feature change like `[-voice]` in a replacement, with the result for every letter of its target category.
+    Change(Vec<String>),
+    TargetStart,
+    TargetEnd
+}
+
+struct Rule {
+    pattern: Vec<Token>,
+    replacement: Vec<Token>,
+    sources: Vec<usize>
+}
+
+struct Match {
+    start: usize,
+    end: usize,
+    in_target: bool,
+    picks: Vec<usize>
+}
+
+fn tokenize(part: &str, categories: &Categories) -> Result<Vec<Token>, String> {
+    let mut tokens = Vec::new();
+    let mut rest = part;
+    while let Some(char) = rest.chars().next() {
+        if let Some((name, len)) = category_at(categories, rest) {
+            tokens.push(Token::Category(name.to_string()));
+            rest = &rest[len..];
+            continue;
+        }
+
+        match char {
+            '{' => return Err(format!("Category not defined: {}", rest.split_once("}").map_or(rest, |(name, _)| &name[1..]))),
+            '#' => tokens.push(Token::WordStart),
+            char if char.is_whitespace() || char == '∅' => {}
+            char => tokens.push(Token::Letter(char))
+        }
+        rest = &rest[char.len_utf8()..];
+    }
+    Ok(tokens)
+}
+
+fn category_names(tokens: &[Token]) -> Vec<&str> {
+    tokens.iter().filter_map(|token| match token {
+        Token::Category(name) => Some(name.as_str()),
+        _ => None
+    }).collect()
+}
+
+fn parse_rule(rule: &str, categories: &Categories, inventory: Option<&Inventory>) -> Result<Rule, String> {
+    let Some((target, rest)) = rule.split_once(">") else {
+        return Err(format!("Rule {rule} is missing a >. Example: a>e/_#"));
+    };
+    let (replacement, environment) = rest.split_once("/").unwrap_or((rest, "_"));
+
+    let Some((before, after)) = environment.split_once("_") else {
+        return Err(format!("The environment of rule {rule} is missing a _"));
+    };
+    if after.contains('_') {
+        return Err(format!("The environment of rule {rule} can only contain one _"));
+    }
+
+    let target = tokenize(target, categories)?;
+    let mut replacement = tokenize(replacement, categories)?;
+    let before = tokenize(before, categories)?;
+    let mut after = tokenize(after, categories)?;
+
+    if target.iter().chain(replacement.iter()).any(|token| matches!(token, Token::WordStart)) {
+        return Err(format!("Rule {rule} can only use # in the environment"));
+    }
+    if target.is_empty() && replacement.is_empty() {
+        return Err(format!("Rule {rule} does not change anything"));
+    }
+    if before.iter().skip(1).chain(after.iter().rev().skip(1)).any(|token| matches!(token, Token::WordStart)) {
+        return Err(format!("Rule {rule} can only use # at the start or end of the environment"));
+    }
+    if let Some(last) = after.last_mut() {
+        if matches!(last, Token::WordStart) {
+            *last = Token::WordEnd;
+        }
+    }
+
+    let target_categories = category_names(&target);
+    let mut sources = Vec::new();
+    let mut changes = Vec::new();
+    for (idx, name) in category_names(&replacement).into_iter().enumerate() {
+        let occurrence = sources.iter().filter(|source| target_categories[**source] == name).count();
+        let source = target_categories.iter().enumerate()
+            .filter(|(_, from)| **from == name)
+            .map(|(source, _)| source)
+            .nth(occurrence)
+            .or((idx < target_categories.len()).then_some(idx));
+        let Some(source) = source else {
+            return Err(format!("Category {name} in the replacement of rule {rule} has no matching category in the target"));
+        };
+
+        let from = target_categories[source];
+        match NaturalClass::parse(name)? {
+            Some(class) => changes.push(Some(categories[from].letters.iter()
+                .map(|letter| class.change(letter, inventory).map(str::to_string))
+                .collect::<Result<Vec<_>, _>>()?)),
+            None if categories[from].letters.len() != categories[name].letters.len() => {
+                return Err(format!("Categories {from} and {name} in rule {rule} must have the same amount of letters"));
+            }
+            None => changes.push(None)
+        }
+        sources.push(source);
+    }
+
+    let mut changes = changes.into_iter();
+    for token in replacement.iter_mut() {
+        if let Token::Category(_) = token {
+            if let Some(letters) = changes.next().flatten() {
+                *token = Token::Change(letters);
+            }
+        }
+    }
+
+    let mut pattern = before;
+    pattern.push(Token::TargetStart);
+    pattern.extend(target);
+    pattern.push(Token::TargetEnd);
+    pattern.extend(after);
+
+    Ok(Rule { pattern, replacement, sources })
+}
+
+impl Rule {
+    fn apply(&self, word: &str, categories: &Categories) -> String {
+        let mut result = String::new();
+        let mut copied = 0;
+        let mut inserted_at = None;
+
+        for start in (0..=word.len()).filter(|start| word.is_char_boundary(*start)) {
+            let mut m = Match { start: 0, end: 0, in_target: false, picks: Vec::new() };
+            if !matches(word, &self.pattern, start, categories, &mut m) {
+                continue;
+            }
+            if m.start < copied || (m.start == m.end && inserted_at == Some(m.start)) {
+                continue;
+            }
+
+            result.push_str(&word[copied..m.start]);
+            let mut sources = self.sources.iter();
+            for token in &self.replacement {
+                match token {
+                    Token::Letter(char) => result.push(*char),
+                    Token::Category(name) => {
+                        let source = sources.next().unwrap();
+                        result.push_str(categories[name].letters[m.picks[*source]]);
+                    }
+                    Token::Change(letters) => {
+                        let source = sources.next().unwrap();
+                        result.push_str(&letters[m.picks[*source]]);
+                    }
+                    _ => {}
+                }
+            }
+            copied = m.end;
+            if m.start == m.end {
+                inserted_at = Some(m.start);
+            }
+        }
+
+        result.push_str(&word[copied..]);
+        result
+    }
+}
+
+fn matches(word: &str, tokens: &[Token], pos: usize, categories: &Categories, m: &mut Match) -> bool {
+    let Some((token, rest)) = tokens.split_first() else {
+        return true;
+    };
+
+    match token {
+        Token::TargetStart => {
+            m.start = pos;
+            m.in_target = true;
+            matches(word, rest, pos, categories, m)
+        }
+        Token::TargetEnd => {
+            m.end = pos;
+            m.in_target = false;
+            let matched = matches(word, rest, pos, categories, m);
+            if !matched {
+                m.in_target = true;
+            }
+            matched
+        }
+        Token::WordStart => pos == 0 && matches(word, rest, pos, categories, m),
+        Token::WordEnd => pos == word.len() && matches(word, rest, pos, categories, m),
+        Token::Letter(char) => word[pos..].starts_with(*char) && matches(word, rest, pos + char.len_utf8(), categories, m),
+        Token::Category(name) => {
+            for (idx, letter) in categories[name].letters.iter().enumerate() {
+                if letter.is_empty() || !word[pos..].starts_with(letter) {
+                    continue;
+                }
+
+                let in_target = m.in_target;
+                if in_target {
+                    m.picks.push(idx);
+                }
+                if matches(word, rest, pos + letter.len(), categories, m) {
+                    return true;
+                }
+                if in_target {
+                    m.picks.pop();
+                }
+            }
+            false
+        }
+        Token::Change(_) => false
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn change(rules: &str, categories: &str, words: &[&str]) -> Vec<String> {
+        let mut parsed = Categories::new();
+        parse_category_option("1", categories, &mut parsed).unwrap();
+        let rules = parse_rules(rules, &mut parsed, None).unwrap();
+        words.iter()
+            .map(|word| rules.iter().fold(word.to_string(), |word, rule| rule.apply(&word, &parsed)))
+            .collect()
+    }
+
+    fn rule_error(rule: &str) -> String {
+        let mut categories = Categories::new();
+        parse_category_option("1", "C:p,t,k;V:a,i;N:m,n", &mut categories).unwrap();
+        match parse_rule(rule, &categories, None) {
+            Ok(_) => panic!("{rule} should not parse"),
+            Err(err) => err
+        }
+    }
+
+    #[test]
+    fn tokenize_reads_categories_boundaries_and_letters() {
+        let mut categories = Categories::new();
+        parse_category_option("1", "V:a,i;{Vn}:ã,ĩ", &mut categories).unwrap();
+        let tokens = tokenize("#tV{Vn} ∅", &categories).unwrap();
+        assert!(matches!(&tokens[..], [Token::WordStart, Token::Letter('t'), Token::Category(v), Token::Category(vn)] if v == "V" && vn == "Vn"));
+        assert_eq!(tokenize("{X}", &categories).err().unwrap(), "Category not defined: X");
+    }
+
+    #[test]
+    fn rules_apply_everywhere_without_an_environment() {
+        assert_eq!(change("k>tʃ", "", &["kaki", "ak"]), ["tʃatʃi", "atʃ"]);
+        assert_eq!(change("sk>ʃ", "", &["skask"]), ["ʃaʃ"]);
+    }
+
+    #[test]
+    fn environments_limit_where_rules_apply() {
+        assert_eq!(change("k>tʃ/_i", "", &["kaki"]), ["katʃi"]);
+        assert_eq!(change("t>d/V_V", "V:a,i", &["tata", "atti"]), ["tada", "atti"]);
+        assert_eq!(change("a>e/i_", "", &["iaa"]), ["iea"]);
+    }
+
+    #[test]
+    fn word_boundaries() {
+        assert_eq!(change("t>d/#_", "", &["tat"]), ["dat"]);
+        assert_eq!(change("t>d/_#", "", &["tat"]), ["tad"]);
+        assert_eq!(change("a>e/#_#", "", &["a", "aa"]), ["e", "aa"]);
+    }
+
+    #[test]
+    fn categories_map_to_categories_of_the_same_size() {
+        assert_eq!(change("C>D/V_V", "C:p,t,k;D:b,d,g;V:a,i", &["apa", "iki", "pa"]), ["aba", "igi", "pa"]);
+        assert_eq!(change("CV>VC", "C:p,t;V:a,i", &["pita"]), ["ipat"]);
+        assert_eq!(rule_error("C>N"), "Categories C and N in rule C>N must have the same amount of letters");
+    }
+
+    #[test]
+    fn deletion_and_insertion() {
+        assert_eq!(change("V>∅/_#", "V:a,i", &["pata", "pit"]), ["pat", "pit"]);
+        assert_eq!(change("h>", "", &["aha"]), ["aa"]);
+        assert_eq!(change("∅>e/#_s", "", &["spa", "asa"]), ["espa", "asa"]);
+        assert_eq!(change(">j/i_a", "", &["ia"]), ["ija"]);
+    }
+
+    #[test]
+    fn rules_apply_in_order() {
+        assert_eq!(change("k>g/V_V; g>ɣ", "V:a,i", &["aka"]), ["aɣa"]);
+    }
+
+    #[test]
+    fn feature_changes() {
+        assert_eq!(change("[-sonorant]>[-voice]/_#", "", &["bad", "baz", "ban"]), ["bat", "bas", "ban"]);
+        assert_eq!(change("C>[+voice]/V_V", "C:p,t,k;V:a,i", &["apa", "ata"]), ["aba", "ada"]);
+    }
+
+    #[test]
+    fn invalid_rules() {
+        assert_eq!(rule_error("a"), "Rule a is missing a >. Example: a>e/_#");
+        assert_eq!(rule_error("a>e/i"), "The environment of rule a>e/i is missing a _");
+        assert_eq!(rule_error("a>e/_i_"), "The environment of rule a>e/_i_ can only contain one _");
+        assert_eq!(rule_error("a>#"), "Rule a># can only use # in the environment");
+        assert_eq!(rule_error("a>e/_#i"), "Rule a>e/_#i can only use # at the start or end of the environment");
+        assert_eq!(rule_error(">"), "Rule > does not change anything");
+        assert_eq!(rule_error("a>V"), "Category V in the replacement of rule a>V has no matching category in the target");
+    }
+}
@@ -0,0 +1,397 @@
+use std::sync::Arc;
+use crate::channel::ConChannel;
+use crate::commands::get_option;
+use crate::commands::word_export::{export, Alphabet};
+use crate::commands::word_import::{detect_format, plan, MAX_IMPORT_SIZE};
+use crate::database::{add_word, count_words, delete_word, get_channel_by_id, get_lexicon, get_paradigm, get_words, get_words_by_headword, update_word, SqlPool};
+use crate::lexicon::LexiconEntry;
+use crate::DatabasePoolKey;
+use serenity::all::{ButtonStyle, ChannelId, CommandDataOption, CommandDataOptionValue, CommandInteraction, CommandOptionType, Context};
+use serenity::builder::{CreateActionRow, CreateAttachment, CreateButton, CreateCommand, CreateCommandOption, CreateEmbed, CreateEmbedFooter, EditInteractionResponse};
+
+const PAGE_SIZE: u64 = 15;
+
+pub fn register() -> CreateCommand {
+    CreateCommand::new("word")
+        .description("Manage the lexicon of this conlang channel")
+        .add_option(CreateCommandOption::new(CommandOptionType::SubCommand, "add", "Add a word to the lexicon")
+            .add_sub_option(word_option())
+            .add_sub_option(CreateCommandOption::new(CommandOptionType::String, "gloss", "Meanings separated by ;. Example: fish; to swim")
+                .max_length(1000)
+                .required(true))
+            .add_sub_option(pronunciation_option())
+            .add_sub_option(part_of_speech_option())
+            .add_sub_option(notes_option())
+            .add_sub_option(paradigm_option()))
+        .add_option(CreateCommandOption::new(CommandOptionType::SubCommand, "edit", "Change a word in the lexicon")
+            .add_sub_option(word_option())
+            .add_sub_option(id_option())
+            .add_sub_option(CreateCommandOption::new(CommandOptionType::String, "new-word", "A new spelling for the word")
+                .max_length(100)
+                .required(false))
+            .add_sub_option(CreateCommandOption::new(CommandOptionType::String, "gloss", "Meanings separated by ;. Example: fish; to swim")
+                .max_length(1000)
+                .required(false))
+            .add_sub_option(pronunciation_option())
+            .add_sub_option(part_of_speech_option())
+            .add_sub_option(notes_option())
+            .add_sub_option(paradigm_option()))
+        .add_option(CreateCommandOption::new(CommandOptionType::SubCommand, "remove", "Remove a word from the lexicon")
+            .add_sub_option(word_option())
+            .add_sub_option(id_option()))
+        .add_option(CreateCommandOption::new(CommandOptionType::SubCommand, "lookup", "Show all entries for a word")
+            .add_sub_option(word_option()))
+        .add_option(CreateCommandOption::new(CommandOptionType::SubCommand, "list", "List the lexicon in alphabetical order")
+            .add_sub_option(CreateCommandOption::new(CommandOptionType::Integer, "page", "The page to show")
+                .min_int_value(1)
+                .required(false)))
+        .add_option(CreateCommandOption::new(CommandOptionType::SubCommand, "export", "Download the lexicon as a file")
+            .add_sub_option(CreateCommandOption::new(CommandOptionType::String, "format", "The file format")
+                .add_string_choice("CSV", "csv")
+                .add_string_choice("JSON", "json")
+                .add_string_choice("Markdown dictionary", "markdown")
+                .add_string_choice("HTML dictionary", "html")
+                .add_string_choice("LIFT (FieldWorks, Lexique Pro)", "lift")
+                .required(true))
+            .add_sub_option(CreateCommandOption::new(CommandOptionType::String, "alphabet", "Letters in sort order, separated by spaces. Example: a b c ch d e")
+                .max_length(1000)
+                .required(false))
+            .add_sub_option(CreateCommandOption::new(CommandOptionType::String, "language-code", "The language code for LIFT. Default: qaa")
+                .max_length(35)
+                .required(false)))
+        .add_option(CreateCommandOption::new(CommandOptionType::SubCommand, "import", "Add or update words from a CSV, TSV or PolyGlot XML file")
+            .add_sub_option(CreateCommandOption::new(CommandOptionType::Attachment, "file", "A spreadsheet with a header row, or PGDictionary.xml from a PolyGlot file")
+                .required(true))
+            .add_sub_option(column_option("word-column", "The column with the words. Default: word or headword"))
+            .add_sub_option(column_option("gloss-column", "The column with the meanings. Default: gloss or meaning"))
+            .add_sub_option(column_option("pronunciation-column", "The column with the pronunciation. Default: pronunciation or IPA"))
+            .add_sub_option(column_option("part-of-speech-column", "The column with the part of speech. Default: part of speech or POS"))
+            .add_sub_option(column_option("notes-column", "The column with notes. Default: notes or etymology")))
+}
+
+fn column_option(name: &str, description: &str) -> CreateCommandOption {
+    CreateCommandOption::new(CommandOptionType::String, name, format!("{description}. Use the header or a number"))
+        .max_length(100)
+        .required(false)
+}
+
+fn word_option() -> CreateCommandOption {
+    CreateCommandOption::new(CommandOptionType::String, "word", "The word as written in the conlang")
+        .max_length(100)
+        .required(true)
+}
+
+fn id_option() -> CreateCommandOption {
+    CreateCommandOption::new(CommandOptionType::Integer, "id", "The entry number shown by /word lookup, for words with several entries")
+        .min_int_value(1)
+        .required(false)
+}
+
+fn pronunciation_option() -> CreateCommandOption {
+    CreateCommandOption::new(CommandOptionType::String, "pronunciation", "The pronunciation in IPA, without slashes")
+        .max_length(100)
+        .required(false)
+}
+
+fn part_of_speech_option() -> CreateCommandOption {
+    CreateCommandOption::new(CommandOptionType::String, "part-of-speech", "Example: noun, verb, adj.")
+        .max_length(50)
+        .required(false)
+}
+
+fn paradigm_option() -> CreateCommandOption {
+    CreateCommandOption::new(CommandOptionType::String, "paradigm", "The inflection class from /paradigm used by /inflect")
+        .max_length(64)
+        .required(false)
+}
+
+fn notes_option() -> CreateCommandOption {
+    CreateCommandOption::new(CommandOptionType::String, "notes", "Etymology, usage or anything else")
+        .max_length(1000)
+        .required(false)
+}
+
+pub async fn run(ctx: &Context, cmd: CommandInteraction) {
+    let _ = cmd.defer(&ctx).await;
+
+    let data = ctx.data.read().await;
+    let database_pool = data.get::<DatabasePoolKey>().unwrap();
+
+    let channel = match get_channel_by_id(database_pool.clone(), cmd.channel_id).await {
+        Ok(channel) => channel,
+        Err(_) => {
+            let _ = cmd.edit_response(&ctx, EditInteractionResponse::new()
+                .content("The lexicon is only available in conlang channels")).await;
+            return;
+        }
+    };
+
+    if let CommandDataOptionValue::SubCommand(options) = &cmd.data.options[0].value {
+        let pool = database_pool.clone();
+        let result = match cmd.data.options[0].name.as_str() {
+            "add" => add(ctx, &cmd, &channel, pool, options).await,
+            "edit" => edit(ctx, &cmd, &channel, pool, options).await,
+            "remove" => remove(ctx, &cmd, &channel, pool, options).await,
+            "lookup" => lookup(ctx, &cmd, pool, options).await,
+            "export" => export_lexicon(ctx, &cmd, pool, options).await,
+            "import" => import(ctx, &cmd, &channel, pool, options).await,
+            "list" => {
+                let page = get_option(options, "page").and_then(|value| value.as_i64()).unwrap_or(1) as u64;
+                match list_page(pool, cmd.channel_id, page - 1).await {
+                    Ok((embed, buttons)) => {
+                        let _ = cmd.edit_response(&ctx, EditInteractionResponse::new()
+                            .embed(embed)
+                            .components(buttons)).await;
+                        Ok(())
+                    }
+                    Err(err) => Err(err)
+                }
+            }
+            _ => Err("Subcommand not implemented".to_string())
+        };
+
+        if let Err(err) = result {
+            let _ = cmd.edit_response(&ctx, EditInteractionResponse::new()
+                .content(err)).await;
+        }
+    }
+}
+
+fn get_string(options: &[CommandDataOption], name: &str) -> Option<String> {
+    get_option(options, name).and_then(|value| value.as_str()).map(|value| value.trim().to_string())
+}
+
+async fn find_entry(pool: Arc<SqlPool>, channel: ChannelId, options: &[CommandDataOption]) -> Result<LexiconEntry, String> {
+    let word = get_string(options, "word").unwrap();
+    let id = get_option(options, "id").and_then(|value| value.as_i64()).map(|id| id as u64);
+
+    let mut entries = get_words_by_headword(pool, channel, &word).await
+        .map_err(|err| format!("Failed to look up {word}: {err}"))?;
+    if let Some(id) = id {
+        entries.retain(|entry| entry.id == id);
+    }
+
+    match entries.len() {
+        0 => Err(format!("Word not found: {word}")),
+        1 => Ok(entries.pop().unwrap()),
+        count => Err(format!("There are {count} entries for {word}. Use the id option to pick one: {}", entries.iter()
+            .map(|entry| format!("#{} ({})", entry.id, entry.glosses().first().unwrap_or(&"")))
+            .collect::<Vec<_>>()
+            .join(", ")))
+    }
+}
+
+async fn check_paradigm(pool: Arc<SqlPool>, channel: ChannelId, paradigm: Option<String>) -> Result<Option<String>, String> {
+    match paradigm {
+        Some(paradigm) => match get_paradigm(pool, channel, &paradigm).await {
+            Ok(_) => Ok(Some(paradigm)),
+            Err(_) => Err(format!("Paradigm not found: {paradigm}. Create it with /paradigm set"))
+        },
+        None => Ok(None)
+    }
+}
+
+async fn add(ctx: &Context, cmd: &CommandInteraction, channel: &ConChannel, pool: Arc<SqlPool>, options: &[CommandDataOption]) -> Result<(), String> {
+    if !channel.check_contributor(ctx, &cmd.user, &cmd.member).await {
+        return Err("Only the channel owner and contributors can edit the lexicon".to_string());
+    }
+
+    let entry = LexiconEntry {
+        id: 0,
+        channel: channel.id,
+        headword: get_string(options, "word").unwrap(),
+        pronunciation: get_string(options, "pronunciation").map(|pronunciation| pronunciation.trim_matches('/').to_string()),
+        part_of_speech: get_string(options, "part-of-speech"),
+        glosses: get_string(options, "gloss").unwrap(),
+        notes: get_string(options, "notes"),
+        paradigm: check_paradigm(pool.clone(), channel.id, get_string(options, "paradigm")).await?,
+        created: 0,
+        updated: 0
+    };
+    let headword = entry.headword.clone();
+    let id = add_word(pool, entry).await.map_err(|err| format!("Failed to add word: {err}"))?;
+
+    let _ = cmd.edit_response(&ctx, EditInteractionResponse::new()
+        .content(format!("Word added: {headword} (#{id})"))).await;
+    Ok(())
+}
+
+async fn edit(ctx: &Context, cmd: &CommandInteraction, channel: &ConChannel, pool: Arc<SqlPool>, options: &[CommandDataOption]) -> Result<(), String> {
+    if !channel.check_contributor(ctx, &cmd.user, &cmd.member).await {
+        return Err("Only the channel owner and contributors can edit the lexicon".to_string());
+    }
+
+    let mut entry = find_entry(pool.clone(), channel.id, options).await?;
+    if let Some(headword) = get_string(options, "new-word") {
+        entry.headword = headword;
+    }
+    if let Some(glosses) = get_string(options, "gloss") {
+        entry.glosses = glosses;
+    }
+    if let Some(pronunciation) = get_string(options, "pronunciation") {
+        entry.pronunciation = Some(pronunciation.trim_matches('/').to_string()).filter(|pronunciation| !pronunciation.is_empty());
+    }
+    if let Some(part_of_speech) = get_string(options, "part-of-speech") {
+        entry.part_of_speech = Some(part_of_speech).filter(|part_of_speech| !part_of_speech.is_empty());
+    }
+    if let Some(notes) = get_string(options, "notes") {
+        entry.notes = Some(notes).filter(|notes| !notes.is_empty());
+    }
+    if let Some(paradigm) = get_string(options, "paradigm") {
+        entry.paradigm = check_paradigm(pool.clone(), channel.id, Some(paradigm).filter(|paradigm| !paradigm.is_empty())).await?;
+    }
+
+    let summary = entry.summary();
+    match update_word(pool, entry).await {
+        Ok(_) => {
+            let _ = cmd.edit_response(&ctx, EditInteractionResponse::new()
+                .content(format!("Word updated: {summary}"))).await;
+            Ok(())
+        }
+        Err(err) => Err(format!("Failed to update word: {err}"))
+    }
+}
+
+async fn remove(ctx: &Context, cmd: &CommandInteraction, channel: &ConChannel, pool: Arc<SqlPool>, options: &[CommandDataOption]) -> Result<(), String> {
+    if !channel.check_contributor(ctx, &cmd.user, &cmd.member).await {
+        return Err("Only the channel owner and contributors can edit the lexicon".to_string());
+    }
+
+    let entry = find_entry(pool.clone(), channel.id, options).await?;
+    match delete_word(pool, channel.id, entry.id).await {
+        Ok(_) => {
+            let _ = cmd.edit_response(&ctx, EditInteractionResponse::new()
+                .content(format!("Word removed: {} (#{})", entry.headword, entry.id))).await;
+            Ok(())
+        }
+        Err(err) => Err(format!("Failed to remove word: {err}"))
+    }
+}
+
+async fn lookup(ctx: &Context, cmd: &CommandInteraction, pool: Arc<SqlPool>, options: &[CommandDataOption]) -> Result<(), String> {
+    let word = get_string(options, "word").unwrap();
+    let entries = get_words_by_headword(pool, cmd.channel_id, &word).await
+        .map_err(|err| format!("Failed to look up {word}: {err}"))?;
+    if entries.is_empty() {
+        return Err(format!("Word not found: {word}"));
+    }
+
+    let embeds = entries.iter().take(10).map(entry_embed).collect();
+    let _ = cmd.edit_response(&ctx, EditInteractionResponse::new()
+        .embeds(embeds)).await;
+    Ok(())
+}
+
+async fn export_lexicon(ctx: &Context, cmd: &CommandInteraction, pool: Arc<SqlPool>, options: &[CommandDataOption]) -> Result<(), String> {
+    let mut entries = get_lexicon(pool, cmd.channel_id).await
+        .map_err(|err| format!("Failed to export lexicon: {err}"))?;
+    if entries.is_empty() {
+        return Err("The lexicon of this channel is empty. Add words with /word add".to_string());
+    }
+
+    let format = get_string(options, "format").unwrap();
+    let alphabet = Alphabet::new(get_string(options, "alphabet").as_deref());
+    let language = get_string(options, "language-code").filter(|code| !code.is_empty()).unwrap_or("qaa".to_string());
+    let title = match cmd.channel_id.name(&ctx).await {
+        Ok(name) => format!("Lexicon of #{name}"),
+        Err(_) => "Lexicon".to_string()
+    };
+
+    alphabet.sort(&mut entries);
+    let (content, filename) = export(&entries, &format, &alphabet, &language, &title);
+    let _ = cmd.edit_response(&ctx, EditInteractionResponse::new()
+        .content(format!("Exported {} words", entries.len()))
+        .new_attachment(CreateAttachment::bytes(content, filename))).await;
+    Ok(())
+}
+
+async fn import(ctx: &Context, cmd: &CommandInteraction, channel: &ConChannel, pool: Arc<SqlPool>, options: &[CommandDataOption]) -> Result<(), String> {
+    if !channel.check_contributor(ctx, &cmd.user, &cmd.member).await {
+        return Err("Only the channel owner and contributors can edit the lexicon".to_string());
+    }
+
+    let attachment = get_option(options, "file")
+        .and_then(|value| value.as_attachment_id())
+        .and_then(|id| cmd.data.resolved.attachments.get(&id))
+        .ok_or("Please attach a CSV, TSV or XML file")?;
+    if attachment.size > MAX_IMPORT_SIZE {
+        return Err("The file is too large".to_string());
+    }
+    let content = match attachment.download().await.map(String::from_utf8) {
+        Ok(Ok(content)) => content,
+        _ => return Err("Failed to read the file. Make sure it is saved as UTF-8".to_string())
+    };
+
+    let format = detect_format(&attachment.filename, &content)?;
+    let requested = ["word-column", "gloss-column", "pronunciation-column", "part-of-speech-column", "notes-column"]
+        .map(|name| get_string(options, name));
+    let plan = plan(pool, channel.id, format, &content, None, &requested).await?;
+    let (embed, buttons) = plan.preview(format);
+
+    let _ = cmd.edit_response(&ctx, EditInteractionResponse::new()
+        .embed(embed)
+        .components(buttons)
+        .new_attachment(CreateAttachment::bytes(content, attachment.filename.clone()))).await;
+    Ok(())
+}
+
+fn entry_embed(entry: &LexiconEntry) -> CreateEmbed {
+    let mut description = String::new();
+    if let Some(pronunciation) = &entry.pronunciation {
+        description.push_str(&format!("/{pronunciation}/ "));
+    }
+    if let Some(part_of_speech) = &entry.part_of_speech {
+        description.push_str(&format!("*{part_of_speech}*"));
+    }
+    for (idx, gloss) in entry.glosses().into_iter().enumerate() {
+        description.push_str(&format!("\n{}. {gloss}", idx + 1));
+    }
+    if let Some(notes) = &entry.notes {
+        description.push_str(&format!("\n\n{notes}"));
+    }
+    if let Some(paradigm) = &entry.paradigm {
+        description.push_str(&format!("\n\nParadigm: {paradigm}"));
+    }
+    description.push_str(&format!("\n\nAdded <t:{}:R>, updated <t:{}:R>", entry.created, entry.updated));
+
+    CreateEmbed::new()
+        .title(&entry.headword)
+        .description(description)
+        .footer(CreateEmbedFooter::new(format!("#{}", entry.id)))
+}
+
+/// A page of the lexicon with buttons to the previous and next page. `page` starts at 0.
+pub async fn list_page(pool: Arc<SqlPool>, channel: ChannelId, page: u64) -> Result<(CreateEmbed, Vec<CreateActionRow>), String> {
+    let total = count_words(pool.clone(), channel).await
+        .map_err(|err| format!("Failed to list words: {err}"))?;
+    if total == 0 {
+        return Err("The lexicon of this channel is empty. Add words with /word add".to_string());
+    }
+
+    let pages = total.div_ceil(PAGE_SIZE);
+    let page = page.min(pages - 1);
+    let entries = get_words(pool, channel, page * PAGE_SIZE, PAGE_SIZE).await
+        .map_err(|err| format!("Failed to list words: {err}"))?;
+
+    let embed = CreateEmbed::new()
+        .title(format!("Lexicon ({total} words)"))
+        .description(entries.iter().map(|entry| {
+            let summary = entry.summary();
+            match summary.char_indices().nth(250) {
+                Some((end, _)) => format!("{}…", &summary[..end]),
+                None => summary
+            }
+        }).collect::<Vec<_>>().join("\n"))
+        .footer(CreateEmbedFooter::new(format!("Page {} of {pages}", page + 1)));
+    let buttons = CreateActionRow::Buttons(vec![
+        CreateButton::new(format!("word-list:{}", page.saturating_sub(1)))
+            .label("Previous")
+            .style(ButtonStyle::Secondary)
+            .disabled(page == 0),
+        CreateButton::new(format!("word-list:{}", page + 1))
+            .label("Next")
+            .style(ButtonStyle::Secondary)
+            .disabled(page + 1 >= pages)
+    ]);
+    Ok((embed, vec![buttons]))
+}
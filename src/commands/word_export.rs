@@ -0,0 +1,213 @@
+use serde_json::{json, Value};
+use serenity::model::Timestamp;
+use crate::commands::wordgen::csv_field;
+use crate::lexicon::LexiconEntry;
+
+/// Orders entries by a custom alphabet like `a b c ch d`. Multi-letter graphemes match before single letters and
+/// characters outside the alphabet sort after it by code point.
+pub struct Alphabet {
+    letters: Vec<String>
+}
+
+impl Alphabet {
+    pub fn new(alphabet: Option<&str>) -> Self {
+        let letters = alphabet.unwrap_or_default()
+            .split([' ', ','])
+            .filter(|letter| !letter.is_empty())
+            .map(str::to_lowercase)
+            .collect();
+        Alphabet { letters }
+    }
+
+    fn tokens(&self, word: &str) -> Vec<(usize, String)> {
+        let word = word.to_lowercase();
+        let mut tokens = Vec::new();
+        let mut rest = word.as_str();
+        while let Some(char) = rest.chars().next() {
+            let letter = self.letters.iter().enumerate()
+                .filter(|(_, letter)| rest.starts_with(letter.as_str()))
+                .max_by_key(|(_, letter)| letter.len());
+            match letter {
+                Some((rank, letter)) => {
+                    tokens.push((rank, letter.clone()));
+                    rest = &rest[letter.len()..];
+                }
+                None => {
+                    tokens.push((self.letters.len() + char as usize, char.to_string()));
+                    rest = &rest[char.len_utf8()..];
+                }
+            }
+        }
+        tokens
+    }
+
+    pub fn sort(&self, entries: &mut [LexiconEntry]) {
+        entries.sort_by_cached_key(|entry| (self.tokens(&entry.headword).into_iter().map(|(rank, _)| rank).collect::<Vec<_>>(), entry.id));
+    }
+
+    fn initial(&self, word: &str) -> String {
+        self.tokens(word).into_iter().next()
+            .map(|(_, letter)| {
+                let mut chars = letter.chars();
+                chars.next().map(|first| first.to_uppercase().chain(chars).collect()).unwrap_or_default()
+            })
+            .unwrap_or_default()
+    }
+}
+
+pub fn export(entries: &[LexiconEntry], format: &str, alphabet: &Alphabet, language: &str, title: &str) -> (String, &'static str) {
+    match format {
+        "json" => (to_json(entries), "lexicon.json"),
+        "markdown" => (to_markdown(entries, alphabet, title), "lexicon.md"),
+        "html" => (to_html(entries, alphabet, title), "lexicon.html"),
+        "lift" => (to_lift(entries, language), "lexicon.lift"),
+        _ => (to_csv(entries), "lexicon.csv")
+    }
+}
+
+fn to_csv(entries: &[LexiconEntry]) -> String {
+    let mut result = "word,pronunciation,part_of_speech,glosses,notes,paradigm\n".to_string();
+    for entry in entries {
+        let fields = [
+            entry.headword.as_str(),
+            entry.pronunciation.as_deref().unwrap_or(""),
+            entry.part_of_speech.as_deref().unwrap_or(""),
+            &entry.glosses().join("; "),
+            entry.notes.as_deref().unwrap_or(""),
+            entry.paradigm.as_deref().unwrap_or("")
+        ];
+        result.push_str(&fields.map(csv_field).join(","));
+        result.push('\n');
+    }
+    result
+}
+
+fn to_json(entries: &[LexiconEntry]) -> String {
+    let entries: Vec<Value> = entries.iter().map(|entry| json!({
+        "id": entry.id,
+        "word": entry.headword,
+        "pronunciation": entry.pronunciation,
+        "part_of_speech": entry.part_of_speech,
+        "glosses": entry.glosses(),
+        "notes": entry.notes,
+        "paradigm": entry.paradigm,
+        "created": entry.created,
+        "updated": entry.updated
+    })).collect();
+    serde_json::to_string_pretty(&entries).unwrap()
+}
+
+fn to_markdown(entries: &[LexiconEntry], alphabet: &Alphabet, title: &str) -> String {
+    let mut result = format!("# {title}\n\n");
+    let mut section = String::new();
+    for entry in entries {
+        let initial = alphabet.initial(&entry.headword);
+        if initial != section {
+            result.push_str(&format!("## {initial}\n\n"));
+            section = initial;
+        }
+
+        result.push_str(&format!("**{}**", entry.headword));
+        if let Some(pronunciation) = &entry.pronunciation {
+            result.push_str(&format!(" /{pronunciation}/"));
+        }
+        if let Some(part_of_speech) = &entry.part_of_speech {
+            result.push_str(&format!(" *{part_of_speech}*"));
+        }
+        let glosses = entry.glosses();
+        if glosses.len() == 1 {
+            result.push_str(&format!(" {}", glosses[0]));
+        } else {
+            for (idx, gloss) in glosses.into_iter().enumerate() {
+                result.push_str(&format!(" **{}.** {gloss}", idx + 1));
+            }
+        }
+        if let Some(notes) = &entry.notes {
+            result.push_str(&format!("\\\n{notes}"));
+        }
+        result.push_str("\n\n");
+    }
+    result
+}
+
+fn to_html(entries: &[LexiconEntry], alphabet: &Alphabet, title: &str) -> String {
+    let mut result = format!("<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n<title>{0}</title>\n<style>\n\
+        body {{ font-family: serif; max-width: 50em; margin: auto; }}\n\
+        dt {{ font-weight: bold; margin-top: 0.5em; }}\n\
+        .pronunciation {{ font-weight: normal; }}\n\
+        .pos {{ font-weight: normal; font-style: italic; }}\n\
+        .notes {{ color: #555; }}\n\
+        </style>\n</head>\n<body>\n<h1>{0}</h1>\n", escape_xml(title));
+
+    let mut section = None;
+    for entry in entries {
+        let initial = alphabet.initial(&entry.headword);
+        if section.as_ref() != Some(&initial) {
+            if section.is_some() {
+                result.push_str("</dl>\n");
+            }
+            result.push_str(&format!("<h2>{}</h2>\n<dl>\n", escape_xml(&initial)));
+            section = Some(initial);
+        }
+
+        result.push_str(&format!("<dt>{}", escape_xml(&entry.headword)));
+        if let Some(pronunciation) = &entry.pronunciation {
+            result.push_str(&format!(" <span class=\"pronunciation\">/{}/</span>", escape_xml(pronunciation)));
+        }
+        if let Some(part_of_speech) = &entry.part_of_speech {
+            result.push_str(&format!(" <span class=\"pos\">{}</span>", escape_xml(part_of_speech)));
+        }
+        result.push_str("</dt>\n<dd>");
+        result.push_str(&entry.glosses().into_iter().map(escape_xml).collect::<Vec<_>>().join("; "));
+        if let Some(notes) = &entry.notes {
+            result.push_str(&format!("<div class=\"notes\">{}</div>", escape_xml(notes)));
+        }
+        result.push_str("</dd>\n");
+    }
+    if section.is_some() {
+        result.push_str("</dl>\n");
+    }
+    result.push_str("</body>\n</html>\n");
+    result
+}
+
+/// LIFT 0.13 as read by FieldWorks and Lexique Pro. Every gloss becomes its own sense.
+fn to_lift(entries: &[LexiconEntry], language: &str) -> String {
+    let language = escape_xml(language);
+    let date = |timestamp: i64| Timestamp::from_unix_timestamp(timestamp)
+        .map(|timestamp| timestamp.to_string())
+        .unwrap_or_default();
+
+    let mut result = "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n<lift version=\"0.13\" producer=\"ConlangBot\">\n".to_string();
+    for entry in entries {
+        result.push_str(&format!("  <entry id=\"{}_{}\" dateCreated=\"{}\" dateModified=\"{}\">\n",
+            escape_xml(&entry.headword), entry.id, date(entry.created), date(entry.updated)));
+        result.push_str(&format!("    <lexical-unit><form lang=\"{language}\"><text>{}</text></form></lexical-unit>\n",
+            escape_xml(&entry.headword)));
+        if let Some(pronunciation) = &entry.pronunciation {
+            result.push_str(&format!("    <pronunciation><form lang=\"{language}-fonipa\"><text>{}</text></form></pronunciation>\n",
+                escape_xml(pronunciation)));
+        }
+        for (idx, gloss) in entry.glosses().into_iter().enumerate() {
+            result.push_str(&format!("    <sense id=\"{}_{}\" order=\"{idx}\">\n", entry.id, idx + 1));
+            if let Some(part_of_speech) = &entry.part_of_speech {
+                result.push_str(&format!("      <grammatical-info value=\"{}\"/>\n", escape_xml(part_of_speech)));
+            }
+            result.push_str(&format!("      <gloss lang=\"en\"><text>{}</text></gloss>\n", escape_xml(gloss)));
+            result.push_str("    </sense>\n");
+        }
+        if let Some(notes) = &entry.notes {
+            result.push_str(&format!("    <note><form lang=\"en\"><text>{}</text></form></note>\n", escape_xml(notes)));
+        }
+        result.push_str("  </entry>\n");
+    }
+    result.push_str("</lift>\n");
+    result
+}
+
+fn escape_xml(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
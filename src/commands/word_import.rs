@@ -0,0 +1,333 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+use csv::ReaderBuilder;
+use roxmltree::{Document, Node};
+use serenity::all::{ButtonStyle, ChannelId};
+use serenity::builder::{CreateActionRow, CreateButton, CreateEmbed};
+use crate::database::{get_lexicon, SqlPool};
+use crate::lexicon::LexiconEntry;
+
+pub const MAX_IMPORT_SIZE: u32 = 2_000_000;
+const MAX_ROWS: usize = 10_000;
+const MAX_ERRORS_SHOWN: usize = 10;
+
+const FIELDS: [&str; 5] = ["word", "gloss", "pronunciation", "part-of-speech", "notes"];
+const HEADERS: [&[&str]; 5] = [
+    &["word", "headword", "conword", "lemma", "entry"],
+    &["gloss", "glosses", "meaning", "meanings", "translation", "english", "localword", "definition"],
+    &["pronunciation", "ipa", "phonemic"],
+    &["partofspeech", "pos", "type", "wordtype", "class", "wordclass"],
+    &["notes", "note", "etymology", "comment", "comments"]
+];
+const LIMITS: [usize; 5] = [100, 1000, 100, 50, 1000];
+
+/// The column of each lexicon field, in the order of `FIELDS`.
+pub struct Mapping {
+    columns: [Option<usize>; 5]
+}
+
+impl Mapping {
+    /// Matches the requested columns (a header or a number starting at 1) against the header row. Fields without a
+    /// requested column use the first header with a known name.
+    fn resolve(header: &[String], requested: &[Option<String>; 5]) -> Result<Self, String> {
+        let normalized: Vec<String> = header.iter().map(|name| normalize(name)).collect();
+        let mut columns = [None; 5];
+        for (idx, field) in FIELDS.iter().enumerate() {
+            if let Some(column) = &requested[idx] {
+                columns[idx] = match column.parse::<usize>() {
+                    Ok(number) if number >= 1 && number <= header.len() => Some(number - 1),
+                    Ok(number) => return Err(format!("{field}: The file has no column {number}")),
+                    Err(_) => match normalized.iter().position(|name| *name == normalize(column)) {
+                        Some(column) => Some(column),
+                        None => return Err(format!("{field}: The file has no column named {column}"))
+                    }
+                };
+            }
+        }
+        for idx in 0..FIELDS.len() {
+            if requested[idx].is_none() {
+                columns[idx] = HEADERS[idx].iter()
+                    .flat_map(|known| normalized.iter().enumerate().filter(move |(_, name)| *name == known))
+                    .map(|(column, _)| column)
+                    .find(|column| !columns.contains(&Some(*column)));
+            }
+        }
+        if columns[0].is_none() {
+            return Err("Could not find the word column. Name it with the word-column option".to_string());
+        }
+        if columns[1].is_none() {
+            return Err("Could not find the gloss column. Name it with the gloss-column option".to_string());
+        }
+        Ok(Mapping { columns })
+    }
+
+    /// Fits the mapping into a button id, like `0.3.1.-.-`.
+    pub fn to_id(&self) -> String {
+        self.columns.iter()
+            .map(|column| column.map(|column| column.to_string()).unwrap_or("-".to_string()))
+            .collect::<Vec<_>>()
+            .join(".")
+    }
+
+    pub fn from_id(id: &str) -> Option<Self> {
+        let mut columns = [None; 5];
+        let mut parts = id.split('.');
+        for column in columns.iter_mut() {
+            *column = match parts.next()? {
+                "-" => None,
+                part => Some(part.parse().ok()?)
+            };
+        }
+        Some(Mapping { columns })
+    }
+
+    fn describe(&self, header: &[String]) -> String {
+        FIELDS.iter().zip(self.columns)
+            .filter_map(|(field, column)| column.map(|column| format!("{field} ← {}", header[column])))
+            .collect::<Vec<_>>()
+            .join(", ")
+    }
+}
+
+fn normalize(name: &str) -> String {
+    name.chars()
+        .filter(|char| char.is_alphanumeric())
+        .flat_map(char::to_lowercase)
+        .collect()
+}
+
+pub fn detect_format(filename: &str, content: &str) -> Result<&'static str, String> {
+    let extension = filename.rsplit_once('.').map(|(_, extension)| extension.to_lowercase()).unwrap_or_default();
+    match extension.as_str() {
+        "csv" => Ok("csv"),
+        "tsv" | "tab" => Ok("tsv"),
+        "xml" => Ok("polyglot"),
+        "pgd" => Err("PolyGlot files are zip archives. Open the .pgd file with a zip program and upload the PGDictionary.xml inside".to_string()),
+        _ if content.lines().next().is_some_and(|line| line.contains('\t')) => Ok("tsv"),
+        _ => Ok("csv")
+    }
+}
+
+/// A row of the file with its number for error messages and its fields in the order of `FIELDS`.
+struct Row {
+    number: usize,
+    fields: [Option<String>; 5]
+}
+
+fn read_table(content: &str, delimiter: u8, mapping: Option<&Mapping>, requested: &[Option<String>; 5])
+              -> Result<(Vec<Row>, Mapping, Vec<String>), String> {
+    let mut reader = ReaderBuilder::new()
+        .has_headers(false)
+        .flexible(true)
+        .delimiter(delimiter)
+        .from_reader(content.trim_start_matches('\u{feff}').as_bytes());
+    let mut records = reader.records();
+
+    let header: Vec<String> = match records.next() {
+        Some(Ok(record)) => record.iter().map(|name| name.trim().to_string()).collect(),
+        Some(Err(err)) => return Err(format!("Failed to read the header row: {err}")),
+        None => return Err("The file is empty".to_string())
+    };
+    let mapping = match mapping {
+        Some(mapping) if mapping.columns.iter().flatten().all(|column| *column < header.len()) => Mapping { columns: mapping.columns },
+        Some(_) => return Err("The file does not match the preview anymore".to_string()),
+        None => Mapping::resolve(&header, requested)?
+    };
+
+    let mut rows = Vec::new();
+    for record in records {
+        let record = record.map_err(|err| format!("Failed to read the file: {err}"))?;
+        let number = record.position().map(|position| position.line() as usize).unwrap_or_default();
+        if record.iter().all(|field| field.trim().is_empty()) {
+            continue;
+        }
+        let fields = mapping.columns.map(|column| column
+            .and_then(|column| record.get(column))
+            .map(|field| field.trim().to_string()));
+        rows.push(Row { number, fields });
+    }
+    Ok((rows, mapping, header))
+}
+
+/// PGDictionary.xml from a PolyGlot .pgd archive. The local word becomes the gloss and the definition the notes, or
+/// the gloss if there is no local word.
+fn read_polyglot(content: &str) -> Result<Vec<Row>, String> {
+    let document = Document::parse(content).map_err(|err| format!("Failed to read the XML: {err}"))?;
+    let text = |node: Node, names: &[&str]| node.children()
+        .find(|child| names.contains(&child.tag_name().name()))
+        .and_then(|child| child.text())
+        .map(|text| strip_tags(text).trim().to_string())
+        .filter(|text| !text.is_empty());
+
+    let classes: HashMap<String, String> = document.descendants()
+        .filter(|node| matches!(node.tag_name().name(), "class" | "wordType"))
+        .filter_map(|node| Some((text(node, &["classId", "wordTypeId"])?, text(node, &["className", "wordTypeName"])?)))
+        .collect();
+
+    let words: Vec<Node> = document.descendants()
+        .filter(|node| node.tag_name().name() == "word" && node.parent_element().is_some_and(|parent| parent.tag_name().name() == "lexicon"))
+        .collect();
+    if words.is_empty() {
+        return Err("No words found. Make sure this is the PGDictionary.xml of a PolyGlot dictionary".to_string());
+    }
+
+    Ok(words.into_iter().enumerate().map(|(idx, word)| {
+        let local = text(word, &["localWord"]);
+        let definition = text(word, &["definition"]);
+        let (gloss, notes) = match local {
+            Some(local) => (Some(local), definition),
+            None => (definition, None)
+        };
+        let part_of_speech = text(word, &["wordTypeId", "classId"])
+            .filter(|id| id != "0")
+            .map(|id| classes.get(&id).cloned().unwrap_or(id));
+        Row {
+            number: idx + 1,
+            fields: [text(word, &["conWord"]), gloss, text(word, &["pronunciation"]), part_of_speech, notes]
+        }
+    }).collect())
+}
+
+fn strip_tags(text: &str) -> String {
+    let mut result = String::with_capacity(text.len());
+    let mut in_tag = false;
+    for char in text.chars() {
+        match char {
+            '<' => in_tag = true,
+            '>' if in_tag => in_tag = false,
+            _ if !in_tag => result.push(char),
+            _ => {}
+        }
+    }
+    result
+}
+
+pub struct ImportPlan {
+    pub entries: Vec<LexiconEntry>,
+    pub mapping: Mapping,
+    pub rows: usize,
+    pub added: usize,
+    pub updated: usize,
+    pub unchanged: usize,
+    pub errors: Vec<String>,
+    columns: String
+}
+
+/// Validates every row and matches it against the lexicon. Rows for words already in the lexicon update the entry
+/// instead of adding another one. Rows with errors are left out of `entries`.
+pub async fn plan(pool: Arc<SqlPool>, channel: ChannelId, format: &str, content: &str, mapping: Option<&Mapping>,
+                  requested: &[Option<String>; 5]) -> Result<ImportPlan, String> {
+    let (rows, mapping, columns) = match format {
+        "polyglot" => (read_polyglot(content)?, Mapping { columns: [None; 5] }, "PolyGlot".to_string()),
+        _ => {
+            let delimiter = if format == "tsv" { b'\t' } else { b',' };
+            let (rows, mapping, header) = read_table(content, delimiter, mapping, requested)?;
+            let columns = mapping.describe(&header);
+            (rows, mapping, columns)
+        }
+    };
+    if rows.is_empty() {
+        return Err("The file has no words".to_string());
+    }
+    if rows.len() > MAX_ROWS {
+        return Err(format!("The file has {} rows. You can import up to {MAX_ROWS} at once", rows.len()));
+    }
+
+    let lexicon = get_lexicon(pool, channel).await
+        .map_err(|err| format!("Failed to read the lexicon: {err}"))?;
+    let mut existing: HashMap<&str, Vec<&LexiconEntry>> = HashMap::new();
+    for entry in &lexicon {
+        existing.entry(entry.headword.as_str()).or_default().push(entry);
+    }
+
+    let mut plan = ImportPlan { entries: Vec::new(), mapping, rows: rows.len(), added: 0, updated: 0, unchanged: 0, errors: Vec::new(), columns };
+    let mut seen: HashMap<String, usize> = HashMap::new();
+    for row in rows {
+        let [headword, glosses, pronunciation, part_of_speech, notes] = row.fields.map(|field| field.filter(|field| !field.is_empty()));
+        let number = row.number;
+
+        let Some(headword) = headword else {
+            plan.errors.push(format!("Row {number}: No word"));
+            continue;
+        };
+        let Some(glosses) = glosses else {
+            plan.errors.push(format!("Row {number}: No gloss for {headword}"));
+            continue;
+        };
+        let fields = [Some(&headword), Some(&glosses), pronunciation.as_ref(), part_of_speech.as_ref(), notes.as_ref()];
+        if let Some(idx) = (0..5).find(|idx| fields[*idx].is_some_and(|field| field.chars().count() > LIMITS[*idx])) {
+            plan.errors.push(format!("Row {number}: The {} of {headword} is longer than {} characters", FIELDS[idx], LIMITS[idx]));
+            continue;
+        }
+        if let Some(first) = seen.get(&headword) {
+            plan.errors.push(format!("Row {number}: {headword} is already in row {first}"));
+            continue;
+        }
+        seen.insert(headword.clone(), number);
+
+        let mut entry = LexiconEntry {
+            id: 0,
+            channel,
+            headword,
+            pronunciation: pronunciation.map(|pronunciation| pronunciation.trim_matches('/').to_string()),
+            part_of_speech,
+            glosses,
+            notes,
+            paradigm: None,
+            created: 0,
+            updated: 0
+        };
+        match existing.get(entry.headword.as_str()).map(Vec::as_slice) {
+            None => plan.added += 1,
+            Some([current]) => {
+                if current.pronunciation == entry.pronunciation && current.part_of_speech == entry.part_of_speech
+                    && current.glosses == entry.glosses && current.notes == entry.notes {
+                    plan.unchanged += 1;
+                    continue;
+                }
+                entry.id = current.id;
+                plan.updated += 1;
+            }
+            Some(current) => {
+                plan.errors.push(format!("Row {number}: {} has {} entries in the lexicon. Edit them with /word edit", entry.headword, current.len()));
+                continue;
+            }
+        }
+        plan.entries.push(entry);
+    }
+    Ok(plan)
+}
+
+impl ImportPlan {
+    pub fn preview(&self, format: &str) -> (CreateEmbed, Vec<CreateActionRow>) {
+        let mut description = format!("Columns: {}", self.columns);
+        if !self.errors.is_empty() {
+            description.push_str("\n\nRows with errors are skipped:");
+            for error in self.errors.iter().take(MAX_ERRORS_SHOWN) {
+                description.push_str(&format!("\n- {error}"));
+            }
+            if self.errors.len() > MAX_ERRORS_SHOWN {
+                description.push_str(&format!("\n…and {} more", self.errors.len() - MAX_ERRORS_SHOWN));
+            }
+        }
+
+        let embed = CreateEmbed::new()
+            .title("Import preview")
+            .description(description)
+            .field("Rows", self.rows.to_string(), true)
+            .field("New words", self.added.to_string(), true)
+            .field("Updated words", self.updated.to_string(), true)
+            .field("Unchanged", self.unchanged.to_string(), true)
+            .field("Errors", self.errors.len().to_string(), true);
+        let buttons = CreateActionRow::Buttons(vec![
+            CreateButton::new(format!("word-import:{format}:{}", self.mapping.to_id()))
+                .label(format!("Import {} words", self.entries.len()))
+                .style(ButtonStyle::Primary)
+                .disabled(self.entries.is_empty()),
+            CreateButton::new("word-import-cancel")
+                .label("Cancel")
+                .style(ButtonStyle::Secondary)
+        ]);
+        (embed, vec![buttons])
+    }
+}
@@ -0,0 +1,97 @@
+use std::sync::Arc;
+use crate::commands::word::list_page;
+use crate::commands::word_import::{plan, Mapping};
+use crate::database::{get_channel_by_id, import_words, SqlPool};
+use crate::DatabasePoolKey;
+use serenity::all::{ComponentInteraction, Context, CreateInteractionResponse};
+use serenity::builder::{CreateInteractionResponseMessage, EditInteractionResponse};
+
+pub async fn run(ctx: &Context, comp: ComponentInteraction) {
+    let id = comp.data.custom_id.as_str();
+    if id.starts_with("word-list:") {
+        list(ctx, &comp).await;
+    } else if id.starts_with("word-import") {
+        import(ctx, &comp).await;
+    }
+}
+
+async fn list(ctx: &Context, comp: &ComponentInteraction) {
+    let data = ctx.data.read().await;
+    let database_pool = data.get::<DatabasePoolKey>().unwrap();
+
+    let page = comp.data.custom_id.strip_prefix("word-list:")
+        .and_then(|page| page.parse::<u64>().ok())
+        .unwrap_or(0);
+
+    let response = match list_page(database_pool.clone(), comp.channel_id, page).await {
+        Ok((embed, buttons)) => CreateInteractionResponse::UpdateMessage(CreateInteractionResponseMessage::new()
+            .embed(embed)
+            .components(buttons)),
+        Err(err) => CreateInteractionResponse::Message(CreateInteractionResponseMessage::new()
+            .content(err)
+            .ephemeral(true))
+    };
+    let _ = comp.create_response(&ctx, response).await;
+}
+
+/// The preview message carries the file, the button the format and column mapping, so imports survive restarts.
+async fn import(ctx: &Context, comp: &ComponentInteraction) {
+    let data = ctx.data.read().await;
+    let database_pool = data.get::<DatabasePoolKey>().unwrap();
+
+    let allowed = match get_channel_by_id(database_pool.clone(), comp.channel_id).await {
+        Ok(channel) => channel.check_contributor(ctx, &comp.user, &comp.member.clone().map(Box::new)).await,
+        Err(_) => false
+    };
+    if !allowed {
+        let _ = comp.create_response(&ctx, CreateInteractionResponse::Message(CreateInteractionResponseMessage::new()
+            .content("Only the channel owner and contributors can edit the lexicon")
+            .ephemeral(true))).await;
+        return;
+    }
+
+    if comp.data.custom_id == "word-import-cancel" {
+        let _ = comp.create_response(&ctx, CreateInteractionResponse::UpdateMessage(CreateInteractionResponseMessage::new()
+            .content("Import cancelled")
+            .embeds(vec![])
+            .components(vec![]))).await;
+        return;
+    }
+
+    let _ = comp.defer(&ctx).await;
+    let content = match run_import(database_pool.clone(), comp).await {
+        Ok(content) => content,
+        Err(err) => format!("Import failed: {err}")
+    };
+    let _ = comp.edit_response(&ctx, EditInteractionResponse::new()
+        .content(content)
+        .embeds(vec![])
+        .components(vec![])).await;
+}
+
+async fn run_import(pool: Arc<SqlPool>, comp: &ComponentInteraction) -> Result<String, String> {
+    let mut parts = comp.data.custom_id.splitn(3, ':').skip(1);
+    let (format, mapping) = match (parts.next(), parts.next().and_then(Mapping::from_id)) {
+        (Some(format), Some(mapping)) => (format, mapping),
+        _ => return Err("Invalid button".to_string())
+    };
+    let attachment = comp.message.attachments.first().ok_or("The file is missing")?;
+    let content = match attachment.download().await.map(String::from_utf8) {
+        Ok(Ok(content)) => content,
+        _ => return Err("Failed to read the file".to_string())
+    };
+
+    let plan = plan(pool.clone(), comp.channel_id, format, &content, Some(&mapping), &Default::default()).await?;
+    let skipped = plan.errors.len();
+    let (added, updated, unchanged) = (plan.added, plan.updated, plan.unchanged);
+    import_words(pool, plan.entries).await.map_err(|err| err.to_string())?;
+
+    let mut content = format!("Imported {added} new and {updated} updated words");
+    if unchanged > 0 {
+        content.push_str(&format!(", {unchanged} unchanged"));
+    }
+    if skipped > 0 {
+        content.push_str(&format!(". Skipped {skipped} rows with errors"));
+    }
+    Ok(content)
+}
@@ -1,44 +1,106 @@
-use std::collections::HashMap;
-use rand::Rng;
-use rand::rngs::ThreadRng;
-use serenity::all::{CommandData, CommandInteraction, CommandOptionType, Context, CreateCommand};
-use serenity::builder::{CreateAttachment, CreateCommandOption, EditInteractionResponse};
+use std::io::Write;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+use flate2::write::GzEncoder;
+use flate2::Compression;
+use rand::{RngExt, SeedableRng};
+use rand::rngs::Xoshiro256PlusPlus;
+use serenity::all::{Attachment, ChannelId, CommandDataOptionValue, CommandInteraction, CommandOptionType, Context, CreateCommand};
+use serenity::builder::{CreateAttachment, CreateCommandOption, CreateEmbed, EditInteractionResponse};
+use serde_json::{json, Value};
+use sqlx::Error;
+use crate::database::get_inventory;
+use crate::orthography::Orthography;
+use crate::phonology::Inventory;
+use crate::preset::find_preset;
+use crate::wordgen::{Definition, Generated, Generator, Word, WordCount};
+use crate::DatabasePoolKey;
+
+pub const MAX_SEED: u64 = (1 << 53) - 1;
+pub const MAX_AMOUNT: usize = 100_000;
+const MAX_UNCOMPRESSED_SIZE: usize = 1_000_000;
+pub const MAX_DEFINITION_SIZE: u32 = 100_000;
 
 pub fn register() -> CreateCommand {
     let mut cmd = CreateCommand::new("wordgen")
         .description("Genarate words based on provided syllables")
         .add_option(CreateCommandOption::new(CommandOptionType::Integer, "amount", "How many words to generate")
             .min_int_value(1)
-            .max_int_value(1000)
+            .max_int_value(MAX_AMOUNT as u64)
             .required(true))
+        .add_option(CreateCommandOption::new(CommandOptionType::String, "preset", "A saved preset to use. Other options override its settings")
+            .max_length(64)
+            .required(false))
         .add_option(CreateCommandOption::new(CommandOptionType::Integer, "min-syllables", "The minimum amount of syllables in a word")
             .min_int_value(1)
             .max_int_value(50)
-            .required(true))
+            .required(false))
         .add_option(CreateCommandOption::new(CommandOptionType::Integer, "max-syllables", "The maximum amount of syllables in a word")
             .min_int_value(1)
             .max_int_value(50)
-            .required(true))
-        .add_option(CreateCommandOption::new(CommandOptionType::String, "syllable", "A list of syllables that can be constructed. Example: CVC,CV(V)(C(!)),C(VC(VVC))V")
-            .required(true));
+            .required(false))
+        .add_option(CreateCommandOption::new(CommandOptionType::String, "syllable", "Syllable types. Example: CVC*2,CV(V)(C(!))%30,[s|ʃ]CV")
+            .required(false))
+        .add_option(CreateCommandOption::new(CommandOptionType::String, "initial-syllable", "Overrides syllable for the first syllable of a word")
+            .required(false))
+        .add_option(CreateCommandOption::new(CommandOptionType::String, "medial-syllable", "Overrides syllable for syllables in the middle of a word")
+            .required(false))
+        .add_option(CreateCommandOption::new(CommandOptionType::String, "final-syllable", "Overrides syllable for the last syllable of a word, including one-syllable words")
+            .required(false));
 
-    for i in 1..=20 {
-        cmd = cmd.add_option(CreateCommandOption::new(CommandOptionType::String, &format!("category-{}", i), "A syllable category. Example: V:a,e,i,o,u")
+    for i in 1..=10 {
+        cmd = cmd.add_option(CreateCommandOption::new(CommandOptionType::String, format!("category-{}", i), "Syllable categories separated by ;. Example: V:a*3,e*2,i,o,u;C:zipf:p,t,k")
             .min_length(3)
-            .max_length(150)
+            .max_length(500)
             .required(false));
     }
 
-    cmd
+    cmd.add_option(CreateCommandOption::new(CommandOptionType::String, "filter", "Forbidden sequences, space separated. #: word edge, .: syllable break, tl>tel: repair")
+            .max_length(500)
+            .required(false))
+        .add_option(CreateCommandOption::new(CommandOptionType::String, "orthography", "Romanization rules separated by ;, shown next to each word. Example: ʃ>sh; tʃ>ch")
+            .max_length(1000)
+            .required(false))
+        .add_option(stress_option())
+        .add_option(CreateCommandOption::new(CommandOptionType::String, "tone", "Tones picked per syllable. Example: high*2,low,falling or ˥,˧,˩")
+            .max_length(500)
+            .required(false))
+        .add_option(CreateCommandOption::new(CommandOptionType::Integer, "seed", "Seed to reproduce a previous word list")
+            .min_int_value(0)
+            .max_int_value(MAX_SEED)
+            .required(false))
+        .add_option(CreateCommandOption::new(CommandOptionType::String, "format", "How the word list is formatted. Defaults to grid")
+            .add_string_choice("Grid", "grid")
+            .add_string_choice("List", "list")
+            .add_string_choice("Syllables", "syllables")
+            .add_string_choice("CSV", "csv")
+            .add_string_choice("JSON", "json")
+            .required(false))
+        .add_option(CreateCommandOption::new(CommandOptionType::Attachment, "definition", "A definition file with categories, patterns and settings. Other options override it")
+            .required(false))
+}
+
+pub fn stress_option() -> CreateCommandOption {
+    CreateCommandOption::new(CommandOptionType::String, "stress", "Which syllable is stressed")
+        .add_string_choice("Initial", "initial")
+        .add_string_choice("Penultimate", "penultimate")
+        .add_string_choice("Final", "final")
+        .add_string_choice("Antepenultimate", "antepenultimate")
+        .add_string_choice("Weight-sensitive", "weight")
+        .add_string_choice("Random", "random")
+        .required(false)
 }
 
 pub async fn run(ctx: &Context, cmd: CommandInteraction) {
     let _ = cmd.defer(&ctx).await;
 
-    match wordgen(&cmd.data).await {
-        Ok(words) => {
+    match wordgen(ctx, &cmd).await {
+        Ok((words, summary)) => {
             let _ = cmd.edit_response(&ctx, EditInteractionResponse::new()
-                .new_attachment(CreateAttachment::bytes(words.as_bytes(), "wordlist.txt"))).await;
+                .content("")
+                .embed(summary)
+                .new_attachment(words)).await;
         }
         Err(err) => {
             let _ = cmd.edit_response(&ctx, EditInteractionResponse::new()
@@ -47,97 +109,271 @@ pub async fn run(ctx: &Context, cmd: CommandInteraction) {
     }
 }
 
-async fn wordgen(data: &CommandData) -> Result<String, String> {
-    let amount = data.options[0].value.as_i64().unwrap();
-    let min_syllables = data.options[1].value.as_i64().unwrap();
-    let max_syllables = data.options[2].value.as_i64().unwrap();
-    let syllable = data.options[3].value.as_str().unwrap();
-
-    let mut categories = HashMap::new();
-    for i in 1..=20 {
-        if let Some(option) = data.options.get(i + 3) {
-            let definition = option.value.as_str().unwrap();
-            if let Some((name, letters)) = definition.split_once(":") {
-                if name.len() != 1 {
-                    return Err(format!("Only single-character names are supported, but category {i} does not match that"));
-                }
+async fn wordgen(ctx: &Context, cmd: &CommandInteraction) -> Result<(CreateAttachment, CreateEmbed), String> {
+    let mut definition = Definition::default();
 
-                let letters: Vec<&str> = letters.split(",").collect();
-                categories.insert(name.chars().next().unwrap(), letters);
-            } else {
-                return Err(format!("Category {i} is not formatted correctly. Example: V:a,e,i,o,u"));
-            }
+    if let Some(option) = cmd.data.options.iter().find(|option| option.name == "preset") {
+        let data = ctx.data.read().await;
+        let database_pool = data.get::<DatabasePoolKey>().unwrap();
+        let name = option.value.as_str().unwrap();
+
+        match find_preset(database_pool.clone(), cmd.user.id, cmd.channel_id, name).await {
+            Ok(preset) => definition.merge(preset.definition()?),
+            Err(Error::RowNotFound) => return Err(format!("Preset not found: {name}")),
+            Err(err) => return Err(format!("Failed to load preset: {err}"))
         }
     }
 
-    for char in syllable.chars() {
-        if char == ',' || char == '(' || char == ')' || char == '!' {
-            continue;
-        }
-        if !categories.contains_key(&char) {
-            return Err(format!("Category not defined: {char}"));
+    let attachment = cmd.data.options.iter()
+        .find(|option| option.name == "definition")
+        .and_then(|option| option.value.as_attachment_id())
+        .and_then(|id| cmd.data.resolved.attachments.get(&id));
+    if let Some(attachment) = attachment {
+        definition.merge(read_definition(attachment).await?);
+    }
+
+    let mut amount = 0;
+    let mut seed = None;
+    let mut format = "grid".to_string();
+    let mut options = Definition::default();
+    for option in &cmd.data.options {
+        match option.name.as_str() {
+            "amount" => amount = option.value.as_i64().unwrap(),
+            "seed" => seed = option.value.as_i64().map(|seed| seed as u64),
+            "format" => format = option.value.as_str().unwrap().to_string(),
+            name => match name.strip_prefix("category-") {
+                Some(i) => options.add_categories(i, option.value.as_str().unwrap()),
+                None => set_definition_option(&mut options, name, &option.value)
+            }
         }
     }
+    definition.merge(options);
 
-    let mut rng = rand::thread_rng();
+    generate_job(ctx, &cmd.token, cmd.channel_id, definition, amount as usize, seed, format).await
+}
 
-    let mut words = Vec::with_capacity(amount as usize);
-    for _ in 0..amount {
-        for _ in 0..10 {
-            let word = generate_word(&mut rng, min_syllables as usize, max_syllables as usize, syllable, &categories);
-            if !words.contains(&word) {
-                words.push(word);
-                break;
+/// Applies an option that is also a setting of definition files, like `syllable` or `stress`.
+pub fn set_definition_option(definition: &mut Definition, name: &str, value: &CommandDataOptionValue) {
+    let text = value.as_str().map(str::to_string);
+    match name {
+        "min-syllables" => definition.min_syllables = value.as_i64(),
+        "max-syllables" => definition.max_syllables = value.as_i64(),
+        "syllable" => definition.syllable = text,
+        "initial-syllable" => definition.initial_syllable = text,
+        "medial-syllable" => definition.medial_syllable = text,
+        "final-syllable" => definition.final_syllable = text,
+        "filter" => definition.filter = text,
+        "orthography" => definition.orthography = text,
+        "stress" => definition.stress = text,
+        "tone" => definition.tone = text,
+        _ => {}
+    }
+}
+
+/// Downloads a UTF-8 text file of at most `MAX_DEFINITION_SIZE` bytes. `name` is used in errors, like `word list`.
+pub async fn read_text(attachment: &Attachment, name: &str) -> Result<String, String> {
+    if attachment.size > MAX_DEFINITION_SIZE {
+        return Err(format!("The {name} is too large"));
+    }
+    match attachment.download().await.map(String::from_utf8) {
+        Ok(Ok(content)) => Ok(content),
+        _ => Err(format!("Failed to read the {name}. Make sure it is a UTF-8 text file"))
+    }
+}
+
+pub async fn read_definition(attachment: &Attachment) -> Result<Definition, String> {
+    Definition::parse_file(&read_text(attachment, "definition file").await?)
+}
+
+/// The phoneme inventory of `channel` if it is `needed`, or `None` if the channel has none.
+pub async fn load_inventory(ctx: &Context, channel: ChannelId, needed: bool) -> Result<Option<Inventory>, String> {
+    if !needed {
+        return Ok(None);
+    }
+
+    let data = ctx.data.read().await;
+    let database_pool = data.get::<DatabasePoolKey>().unwrap();
+    match get_inventory(database_pool.clone(), channel).await {
+        Ok(inventory) => Ok(Some(inventory)),
+        Err(Error::RowNotFound) => Ok(None),
+        Err(err) => Err(format!("Failed to load the phoneme inventory: {err}"))
+    }
+}
+
+/// Generates words on the blocking thread pool and edits the deferred response with the progress while it runs.
+/// Phoneme classes like `@stops` or `@[+nasal]` are resolved with the inventory of `channel`.
+pub async fn generate_job(ctx: &Context, token: &str, channel: ChannelId, mut definition: Definition, amount: usize,
+                          seed: Option<u64>, format: String) -> Result<(CreateAttachment, CreateEmbed), String> {
+    let inventory = load_inventory(ctx, channel, definition.uses_inventory()).await?;
+    definition.expand_classes(inventory.as_ref())?;
+
+    let progress = Arc::new(AtomicUsize::new(0));
+    let worker_progress = progress.clone();
+    let mut job = tokio::task::spawn_blocking(move || {
+        let (words, file_name, summary) = generate_list(&definition, amount, seed, &format, &worker_progress)?;
+        Ok((compress(words, file_name), summary))
+    });
+
+    loop {
+        tokio::select! {
+            result = &mut job => return result.unwrap_or_else(|err| Err(format!("Word generation failed: {err}"))),
+            _ = tokio::time::sleep(Duration::from_secs(3)) => {
+                let update = EditInteractionResponse::new()
+                    .content(format!("Generating words... {}/{amount}", progress.load(Ordering::Relaxed)));
+                let _ = ctx.http.edit_original_interaction_response(token, &update, Vec::new()).await;
             }
         }
     }
+}
 
-    let longest_word = words.iter().map(|word| word.len()).max().unwrap_or(0) + 2;
+fn compress(words: String, file_name: &str) -> CreateAttachment {
+    if words.len() <= MAX_UNCOMPRESSED_SIZE {
+        return CreateAttachment::bytes(words, file_name);
+    }
 
-    let mut result = String::new();
-    for (idx, word) in words.into_iter().enumerate() {
-        result.push_str(word.as_str());
-        if idx % 5 == 4 {
-            result.push_str("\n");
-        } else {
-            result.push_str(" ".repeat(longest_word - word.len()).as_str());
-        }
+    let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+    match encoder.write_all(words.as_bytes()).and_then(|_| encoder.finish()) {
+        Ok(compressed) => CreateAttachment::bytes(compressed, format!("{file_name}.gz")),
+        Err(_) => CreateAttachment::bytes(words, file_name)
     }
-    Ok(result)
 }
 
-fn generate_word(rng: &mut ThreadRng, min_syllables: usize, max_syllables: usize,
-                       syllable: &str, categories: &HashMap<char, Vec<&str>>) -> String {
-    let mut word = String::new();
-    let syllable_count = rng.gen_range(min_syllables..=max_syllables);
+fn generate_list(definition: &Definition, amount: usize, seed: Option<u64>, format: &str,
+                     progress: &AtomicUsize) -> Result<(String, &'static str, CreateEmbed), String> {
+    let generator = definition.compile()?;
+
+    let seed = seed.unwrap_or_else(|| rand::rng().random_range(0..=MAX_SEED));
+    let mut rng = Xoshiro256PlusPlus::seed_from_u64(seed);
+    let generated = generator.generate(&mut rng, amount, |count| progress.store(count, Ordering::Relaxed));
+
+    let (result, file_name) = format_words(&generated.words, format, generator.orthography.as_ref());
+    Ok((result, file_name, summary(&generator, &generated, amount, seed)))
+}
+
+fn summary(generator: &Generator, generated: &Generated, amount: usize, seed: u64) -> CreateEmbed {
+    let words = &generated.words;
+    let (possible, limit) = match generator.count_words() {
+        WordCount::Exact(count) => (format!("{count}"), count as f64),
+        WordCount::AtMost(count) if count >= 1e15 => (format!("At most {count:.2e}"), count),
+        WordCount::AtMost(count) => (format!("At most {count}"), count)
+    };
+
+    let mut embed = CreateEmbed::new()
+        .title("Word Generation")
+        .field("Seed", seed.to_string(), true)
+        .field("Words", format!("{} of {amount}", words.len()), true)
+        .field("Possible words", possible, true)
+        .field("Candidates", format!("{} ({} duplicates, {} rejected, {} repaired)",
+            words.len() + generated.duplicates + generated.rejected,
+            generated.duplicates, generated.rejected, generated.repaired), false);
+
+    if limit < amount as f64 {
+        embed = embed.description(format!("These settings can only produce {limit} distinct words, fewer than the {amount} requested"));
+    } else if words.len() < amount {
+        embed = embed.description("Fewer words than requested were generated because most candidates were duplicates or filtered out. \
+            Add more phonemes, syllable types or syllables to get more words");
+    }
+
+    if !words.is_empty() {
+        let letters: usize = words.iter().map(|word| word.phonemes().chars().count()).sum();
+        let syllables: usize = words.iter().map(|word| word.boundaries.len() + 1).sum();
+        embed = embed.field("Average length", format!("{:.1} letters, {:.1} syllables",
+            letters as f64 / words.len() as f64, syllables as f64 / words.len() as f64), false);
+
+        let frequencies = generator.phoneme_frequencies(words);
+        let total: usize = frequencies.iter().map(|(_, count)| count).sum();
+        let mut field = String::new();
+        for (phoneme, count) in frequencies {
+            let entry = format!("{phoneme} {:.1}%", count as f64 * 100.0 / total as f64);
+            if field.len() + entry.len() + 3 > 1024 {
+                break;
+            }
+            if !field.is_empty() {
+                field.push_str(" · ");
+            }
+            field.push_str(&entry);
+        }
+        embed = embed.field("Phoneme frequencies", field, false);
+    }
+    embed
+}
 
-    for _ in 0..syllable_count {
-        let syllable: Vec<&str> = syllable.split(",").collect();
-        let syllable = syllable.get(rng.gen_range(0..syllable.len())).unwrap();
+fn format_words(words: &[Word], format: &str, orthography: Option<&Orthography>) -> (String, &'static str) {
+    let romanized: Vec<Option<String>> = words.iter()
+        .map(|word| orthography.map(|orthography| orthography.apply(&word.text)))
+        .collect();
+    let side_by_side = |phonemic: String, romanized: &Option<String>| match romanized {
+        Some(romanized) => format!("{romanized} /{phonemic}/"),
+        None => phonemic
+    };
 
-        let mut skipping = 0u8;
-        for char in syllable.chars() {
-            if char == '(' {
-                if skipping > 0 || rng.gen() {
-                    skipping += 1;
+    match format {
+        "list" => (words.iter().zip(&romanized)
+            .map(|(word, romanized)| side_by_side(word.text.clone(), romanized))
+            .collect::<Vec<_>>().join("\n"), "wordlist.txt"),
+        "syllables" => (words.iter().zip(&romanized)
+            .map(|(word, romanized)| side_by_side(word.syllables().join("."), romanized))
+            .collect::<Vec<_>>().join("\n"), "wordlist.txt"),
+        "csv" => {
+            let mut result = "word,syllables,patterns".to_string();
+            if orthography.is_some() {
+                result.push_str(",romanized");
+            }
+            result.push('\n');
+            for (word, romanized) in words.iter().zip(&romanized) {
+                result.push_str(&csv_field(&word.text));
+                result.push(',');
+                result.push_str(&csv_field(&word.syllables().join(".")));
+                result.push(',');
+                result.push_str(&csv_field(&word.sources.join(" ")));
+                if let Some(romanized) = romanized {
+                    result.push(',');
+                    result.push_str(&csv_field(romanized));
                 }
-            } else if char == ')' {
-                if skipping > 0 {
-                    skipping -= 1;
+                result.push('\n');
+            }
+            (result, "wordlist.csv")
+        }
+        "json" => {
+            let words: Vec<Value> = words.iter().zip(&romanized).map(|(word, romanized)| {
+                let mut value = json!({
+                    "word": word.text,
+                    "syllables": word.syllables().into_iter().zip(&word.sources).map(|(text, pattern)| json!({
+                        "text": text,
+                        "pattern": pattern
+                    })).collect::<Vec<_>>()
+                });
+                if let Some(romanized) = romanized {
+                    value["romanized"] = json!(romanized);
                 }
-            } else if skipping == 0 {
-                if char == '!' {
-                    if let Some(last) = word.chars().last() {
-                        word.push(last);
-                    }
+                value
+            }).collect();
+            (serde_json::to_string_pretty(&words).unwrap(), "wordlist.json")
+        }
+        _ => {
+            let cells: Vec<String> = words.iter().zip(&romanized)
+                .map(|(word, romanized)| side_by_side(word.text.clone(), romanized))
+                .collect();
+            let longest_word = cells.iter().map(|cell| cell.chars().count()).max().unwrap_or(0) + 2;
+
+            let mut result = String::new();
+            for (idx, cell) in cells.iter().enumerate() {
+                result.push_str(cell);
+                if idx % 5 == 4 {
+                    result.push('\n');
                 } else {
-                    let letters = categories.get(&char).unwrap();
-                    let letter = letters.get(rng.gen_range(0..letters.len())).unwrap();
-                    word.push_str(letter);
+                    result.push_str(" ".repeat(longest_word - cell.chars().count()).as_str());
                 }
             }
+            (result, "wordlist.txt")
         }
     }
+}
 
-    word
-}
\ No newline at end of file
+pub fn csv_field(field: &str) -> String {
+    if field.contains([',', '"', '\n']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
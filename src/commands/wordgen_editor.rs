@@ -0,0 +1,26 @@
+use serenity::all::{CommandInteraction, Context, CreateCommand, InputTextStyle};
+use serenity::builder::{CreateActionRow, CreateInputText, CreateInteractionResponse, CreateModal};
+
+pub fn register() -> CreateCommand {
+    CreateCommand::new("wordgen-editor")
+        .description("Generate words from a definition typed into a form")
+}
+
+pub async fn run(ctx: &Context, cmd: CommandInteraction) {
+    let _ = cmd.create_response(&ctx, CreateInteractionResponse::Modal(
+        CreateModal::new("wordgen-editor", "Generate Words")
+            .components(vec![
+                CreateActionRow::InputText(CreateInputText::new(InputTextStyle::Short, "Amount", "0")
+                    .required(true)
+                    .max_length(6)
+                    .value("100")),
+                CreateActionRow::InputText(CreateInputText::new(InputTextStyle::Paragraph, "Definition", "1")
+                    .required(true)
+                    .max_length(4000)
+                    .placeholder("min-syllables = 1\nmax-syllables = 3\nsyllable = CV(C)\nC:p,t,k,m,n\nV:a,e,i,o,u")),
+                CreateActionRow::InputText(CreateInputText::new(InputTextStyle::Short, "Seed", "2")
+                    .required(false)
+                    .max_length(16))
+            ])
+    )).await;
+}
@@ -0,0 +1,112 @@
+use crate::commands::wordgen::{generate_job, MAX_AMOUNT, MAX_DEFINITION_SIZE, MAX_SEED};
+use crate::database::save_preset;
+use crate::preset::WordgenPreset;
+use crate::wordgen::import::import;
+use crate::DatabasePoolKey;
+use serenity::all::{CommandInteraction, CommandOptionType, Context, CreateCommand};
+use serenity::builder::{CreateAttachment, CreateCommandOption, EditInteractionResponse};
+
+pub fn register() -> CreateCommand {
+    CreateCommand::new("wordgen-import")
+        .description("Convert settings from another word generator into a wordgen definition")
+        .add_option(CreateCommandOption::new(CommandOptionType::String, "format", "The generator the settings come from")
+            .add_string_choice("Awkwords", "awkwords")
+            .add_string_choice("Gen (zompist)", "gen")
+            .add_string_choice("Lexifer / Vulgarlang-style", "lexifer")
+            .required(true))
+        .add_option(CreateCommandOption::new(CommandOptionType::Attachment, "file", "A text file with the exported or copied settings")
+            .required(true))
+        .add_option(CreateCommandOption::new(CommandOptionType::Integer, "amount", "Generate this many words with the imported settings")
+            .min_int_value(1)
+            .max_int_value(MAX_AMOUNT as u64)
+            .required(false))
+        .add_option(CreateCommandOption::new(CommandOptionType::String, "save-as", "Save the imported settings as a preset with this name")
+            .max_length(64)
+            .required(false))
+        .add_option(CreateCommandOption::new(CommandOptionType::Integer, "seed", "Seed to reproduce a previous word list")
+            .min_int_value(0)
+            .max_int_value(MAX_SEED)
+            .required(false))
+}
+
+pub async fn run(ctx: &Context, cmd: CommandInteraction) {
+    let _ = cmd.defer(&ctx).await;
+
+    match wordgen_import(ctx, &cmd).await {
+        Ok(response) => {
+            let _ = cmd.edit_response(&ctx, response).await;
+        }
+        Err(err) => {
+            let _ = cmd.edit_response(&ctx, EditInteractionResponse::new()
+                .content(err)).await;
+        }
+    }
+}
+
+async fn wordgen_import(ctx: &Context, cmd: &CommandInteraction) -> Result<EditInteractionResponse, String> {
+    let mut format = "";
+    let mut amount = None;
+    let mut name = None;
+    let mut seed = None;
+    for option in &cmd.data.options {
+        match option.name.as_str() {
+            "format" => format = option.value.as_str().unwrap(),
+            "amount" => amount = option.value.as_i64().map(|amount| amount as usize),
+            "save-as" => name = option.value.as_str(),
+            "seed" => seed = option.value.as_i64().map(|seed| seed as u64),
+            _ => {}
+        }
+    }
+
+    let attachment = cmd.data.options.iter()
+        .find(|option| option.name == "file")
+        .and_then(|option| option.value.as_attachment_id())
+        .and_then(|id| cmd.data.resolved.attachments.get(&id))
+        .ok_or("Please attach the settings as a text file")?;
+    if attachment.size > MAX_DEFINITION_SIZE {
+        return Err("The file is too large".to_string());
+    }
+    let content = match attachment.download().await.map(String::from_utf8) {
+        Ok(Ok(content)) => content,
+        _ => return Err("Failed to read the file. Make sure it is a UTF-8 text file".to_string())
+    };
+
+    let imported = import(format, &content);
+    let definition = imported.definition;
+
+    let mut note = format!("Imported {} categories", definition.categories.len());
+    if !imported.warnings.is_empty() {
+        note.push_str("\nNot imported:");
+        for warning in imported.warnings.iter().take(15) {
+            note.push_str(&format!("\n- {warning}"));
+        }
+        if imported.warnings.len() > 15 {
+            note.push_str(&format!("\n- and {} more", imported.warnings.len() - 15));
+        }
+    }
+
+    if let Some(name) = name {
+        definition.compile()?;
+
+        let data = ctx.data.read().await;
+        let database_pool = data.get::<DatabasePoolKey>().unwrap();
+        save_preset(database_pool.clone(), WordgenPreset {
+            owner: cmd.user.id,
+            name: name.to_string(),
+            channel: None,
+            definition: definition.to_file()
+        }).await.map_err(|err| format!("Failed to save preset: {err}"))?;
+        note.push_str(&format!("\nPreset saved: {name}"));
+    }
+
+    let mut response = EditInteractionResponse::new()
+        .new_attachment(CreateAttachment::bytes(definition.to_file().into_bytes(), "definition.txt"));
+    if let Some(amount) = amount {
+        let (words, summary) = generate_job(ctx, &cmd.token, cmd.channel_id, definition, amount, seed, "grid".to_string()).await?;
+        response = response
+            .embed(summary)
+            .new_attachment(words);
+    }
+
+    Ok(response.content(note))
+}
@@ -0,0 +1,52 @@
+use crate::commands::wordgen::{generate_job, MAX_AMOUNT, MAX_SEED};
+use crate::wordgen::Definition;
+use serenity::all::{ActionRowComponent, Context, ModalInteraction};
+use serenity::builder::EditInteractionResponse;
+
+pub async fn run(ctx: &Context, modal: ModalInteraction) {
+    let _ = modal.defer(&ctx).await;
+
+    let values: Vec<&str> = modal.data.components.iter()
+        .filter_map(|row| row.components.first())
+        .map(|component| match component {
+            ActionRowComponent::InputText(txt) => txt.value.as_deref().unwrap_or(""),
+            _ => ""
+        })
+        .collect();
+    if values.len() != 3 {
+        return;
+    }
+
+    let result = match parse(values[0], values[1], values[2]) {
+        Ok((definition, amount, seed)) => generate_job(ctx, &modal.token, modal.channel_id, definition, amount, seed, "grid".to_string()).await,
+        Err(err) => Err(err)
+    };
+    match result {
+        Ok((words, summary)) => {
+            let _ = modal.edit_response(&ctx, EditInteractionResponse::new()
+                .content("")
+                .embed(summary)
+                .new_attachment(words)).await;
+        }
+        Err(err) => {
+            let _ = modal.edit_response(&ctx, EditInteractionResponse::new()
+                .content(err)).await;
+        }
+    }
+}
+
+fn parse(amount: &str, definition: &str, seed: &str) -> Result<(Definition, usize, Option<u64>), String> {
+    let amount = match amount.trim().parse::<usize>() {
+        Ok(amount) if (1..=MAX_AMOUNT).contains(&amount) => amount,
+        _ => return Err(format!("The amount must be a number between 1 and {MAX_AMOUNT}"))
+    };
+    let seed = match seed.trim() {
+        "" => None,
+        seed => match seed.parse::<u64>() {
+            Ok(seed) if seed <= MAX_SEED => Some(seed),
+            _ => return Err(format!("The seed must be a number between 0 and {MAX_SEED}"))
+        }
+    };
+
+    Ok((Definition::parse_file(definition)?, amount, seed))
+}
@@ -0,0 +1,229 @@
+use crate::commands::get_option;
+use crate::commands::wordgen::{load_inventory, read_definition, set_definition_option, stress_option};
+use crate::database::{delete_preset, get_channel_by_id, get_presets_by_channel, get_presets_by_owner, save_preset, share_preset};
+use crate::preset::{find_preset, WordgenPreset};
+use crate::wordgen::Definition;
+use crate::DatabasePoolKey;
+use serenity::all::{CommandDataOption, CommandDataOptionValue, CommandInteraction, CommandOptionType, Context};
+use serenity::builder::{CreateAttachment, CreateCommand, CreateCommandOption, CreateEmbed, EditInteractionResponse};
+use sqlx::Error;
+
+pub fn register() -> CreateCommand {
+    CreateCommand::new("wordgen-preset")
+        .description("Manage saved /wordgen settings")
+        .add_option(CreateCommandOption::new(CommandOptionType::SubCommand, "save", "Save or overwrite a preset")
+            .add_sub_option(CreateCommandOption::new(CommandOptionType::String, "name", "The name of the preset")
+                .max_length(64)
+                .required(true))
+            .add_sub_option(CreateCommandOption::new(CommandOptionType::Integer, "min-syllables", "The minimum amount of syllables in a word")
+                .min_int_value(1)
+                .max_int_value(50)
+                .required(false))
+            .add_sub_option(CreateCommandOption::new(CommandOptionType::Integer, "max-syllables", "The maximum amount of syllables in a word")
+                .min_int_value(1)
+                .max_int_value(50)
+                .required(false))
+            .add_sub_option(CreateCommandOption::new(CommandOptionType::String, "syllable", "Syllable types. Example: CVC*2,CV(V)(C(!))%30,[s|ʃ]CV")
+                .required(false))
+            .add_sub_option(CreateCommandOption::new(CommandOptionType::String, "initial-syllable", "Overrides syllable for the first syllable of a word")
+                .required(false))
+            .add_sub_option(CreateCommandOption::new(CommandOptionType::String, "medial-syllable", "Overrides syllable for syllables in the middle of a word")
+                .required(false))
+            .add_sub_option(CreateCommandOption::new(CommandOptionType::String, "final-syllable", "Overrides syllable for the last syllable of a word, including one-syllable words")
+                .required(false))
+            .add_sub_option(CreateCommandOption::new(CommandOptionType::String, "categories", "Syllable categories separated by ;. Example: V:a*3,e*2,i,o,u;C:zipf:p,t,k")
+                .max_length(4000)
+                .required(false))
+            .add_sub_option(CreateCommandOption::new(CommandOptionType::String, "filter", "Forbidden sequences, space separated. #: word edge, .: syllable break, tl>tel: repair")
+                .max_length(500)
+                .required(false))
+            .add_sub_option(CreateCommandOption::new(CommandOptionType::String, "orthography", "Romanization rules separated by ;, shown next to each word. Example: ʃ>sh; tʃ>ch")
+                .max_length(1000)
+                .required(false))
+            .add_sub_option(stress_option())
+            .add_sub_option(CreateCommandOption::new(CommandOptionType::String, "tone", "Tones picked per syllable. Example: high*2,low,falling or ˥,˧,˩")
+                .max_length(500)
+                .required(false))
+            .add_sub_option(CreateCommandOption::new(CommandOptionType::Attachment, "definition", "A definition file with categories, patterns and settings. Other options override it")
+                .required(false)))
+        .add_option(CreateCommandOption::new(CommandOptionType::SubCommand, "load", "Show the settings of a preset")
+            .add_sub_option(CreateCommandOption::new(CommandOptionType::String, "name", "The name of the preset")
+                .max_length(64)
+                .required(true)))
+        .add_option(CreateCommandOption::new(CommandOptionType::SubCommand, "list", "List your presets and the presets shared in this channel"))
+        .add_option(CreateCommandOption::new(CommandOptionType::SubCommand, "delete", "Delete one of your presets")
+            .add_sub_option(CreateCommandOption::new(CommandOptionType::String, "name", "The name of the preset")
+                .max_length(64)
+                .required(true)))
+        .add_option(CreateCommandOption::new(CommandOptionType::SubCommand, "share", "Share one of your presets with everyone in this channel")
+            .add_sub_option(CreateCommandOption::new(CommandOptionType::String, "name", "The name of the preset")
+                .max_length(64)
+                .required(true))
+            .add_sub_option(CreateCommandOption::new(CommandOptionType::Boolean, "shared", "Whether the preset should be shared. Defaults to true")
+                .required(false)))
+}
+
+pub async fn run(ctx: &Context, cmd: CommandInteraction) {
+    let _ = cmd.defer_ephemeral(&ctx).await;
+
+    if let CommandDataOptionValue::SubCommand(options) = &cmd.data.options[0].value {
+        let result = match cmd.data.options[0].name.as_str() {
+            "save" => save(ctx, &cmd, options).await,
+            "load" => load(ctx, &cmd, options).await,
+            "list" => list(ctx, &cmd).await,
+            "delete" => delete(ctx, &cmd, options).await,
+            "share" => share(ctx, &cmd, options).await,
+            _ => Err("Subcommand not implemented".to_string())
+        };
+
+        if let Err(err) = result {
+            let _ = cmd.edit_response(&ctx, EditInteractionResponse::new()
+                .content(err)).await;
+        }
+    }
+}
+
+async fn save(ctx: &Context, cmd: &CommandInteraction, options: &[CommandDataOption]) -> Result<(), String> {
+    let data = ctx.data.read().await;
+    let database_pool = data.get::<DatabasePoolKey>().unwrap();
+
+    let name = get_option(options, "name").and_then(|value| value.as_str()).unwrap();
+    let mut definition = Definition::default();
+    let attachment = get_option(options, "definition")
+        .and_then(|value| value.as_attachment_id())
+        .and_then(|id| cmd.data.resolved.attachments.get(&id));
+    if let Some(attachment) = attachment {
+        definition = read_definition(attachment).await?;
+    }
+
+    let mut settings = Definition::default();
+    for option in options {
+        match option.name.as_str() {
+            "categories" => settings.add_categories("list", option.value.as_str().unwrap()),
+            name => set_definition_option(&mut settings, name, &option.value)
+        }
+    }
+    definition.merge(settings);
+
+    // Check the preset the way /wordgen would use it here, so mistakes show up now and not when it is used
+    let inventory = load_inventory(ctx, cmd.channel_id, definition.uses_inventory()).await?;
+    let mut expanded = definition.clone();
+    expanded.expand_classes(inventory.as_ref())?;
+    expanded.compile()?;
+
+    save_preset(database_pool.clone(), WordgenPreset {
+        owner: cmd.user.id,
+        name: name.to_string(),
+        channel: None,
+        definition: definition.to_file()
+    }).await.map_err(|err| format!("Failed to save preset: {err}"))?;
+
+    let _ = cmd.edit_response(&ctx, EditInteractionResponse::new()
+        .content(format!("Preset saved: {name}"))).await;
+    Ok(())
+}
+
+async fn load(ctx: &Context, cmd: &CommandInteraction, options: &[CommandDataOption]) -> Result<(), String> {
+    let data = ctx.data.read().await;
+    let database_pool = data.get::<DatabasePoolKey>().unwrap();
+
+    let name = get_option(options, "name").and_then(|value| value.as_str()).unwrap();
+    let preset = match find_preset(database_pool.clone(), cmd.user.id, cmd.channel_id, name).await {
+        Ok(preset) => preset,
+        Err(Error::RowNotFound) => return Err(format!("Preset not found: {name}")),
+        Err(err) => return Err(format!("Failed to load preset: {err}"))
+    };
+
+    let description = format!("```\n{}```", preset.definition);
+    let inline = description.chars().count() <= 4096 && !preset.definition.contains("```");
+    let mut embed = CreateEmbed::new()
+        .title(format!("Preset: {}", preset.name))
+        .field("Owner", format!("<@{}>", preset.owner), true);
+    if inline {
+        embed = embed.description(description);
+    }
+    if let Some(channel) = preset.channel() {
+        embed = embed.field("Shared in", format!("<#{channel}>"), true);
+    }
+
+    let mut response = EditInteractionResponse::new()
+        .add_embed(embed);
+    if !inline {
+        response = response.new_attachment(CreateAttachment::bytes(preset.definition.into_bytes(), "definition.txt"));
+    }
+    let _ = cmd.edit_response(&ctx, response).await;
+    Ok(())
+}
+
+async fn list(ctx: &Context, cmd: &CommandInteraction) -> Result<(), String> {
+    let data = ctx.data.read().await;
+    let database_pool = data.get::<DatabasePoolKey>().unwrap();
+
+    let own = get_presets_by_owner(database_pool.clone(), cmd.user.id).await
+        .map_err(|err| format!("Failed to list presets: {err}"))?;
+    let shared = get_presets_by_channel(database_pool.clone(), cmd.channel_id).await
+        .map_err(|err| format!("Failed to list presets: {err}"))?;
+
+    let format_list = |presets: Vec<WordgenPreset>| if presets.is_empty() {
+        "None".to_string()
+    } else {
+        presets.into_iter().map(|preset| preset.name).collect::<Vec<_>>().join(", ")
+    };
+
+    let _ = cmd.edit_response(&ctx, EditInteractionResponse::new()
+        .add_embed(CreateEmbed::new()
+            .title("Wordgen Presets")
+            .field("Your presets", format_list(own), false)
+            .field("Shared in this channel", format_list(shared), false))).await;
+    Ok(())
+}
+
+async fn delete(ctx: &Context, cmd: &CommandInteraction, options: &[CommandDataOption]) -> Result<(), String> {
+    let data = ctx.data.read().await;
+    let database_pool = data.get::<DatabasePoolKey>().unwrap();
+
+    let name = get_option(options, "name").and_then(|value| value.as_str()).unwrap();
+    match delete_preset(database_pool.clone(), cmd.user.id, name).await {
+        Ok(true) => {
+            let _ = cmd.edit_response(&ctx, EditInteractionResponse::new()
+                .content(format!("Preset deleted: {name}"))).await;
+            Ok(())
+        }
+        Ok(false) => Err(format!("You don't have a preset named {name}")),
+        Err(err) => Err(format!("Failed to delete preset: {err}"))
+    }
+}
+
+async fn share(ctx: &Context, cmd: &CommandInteraction, options: &[CommandDataOption]) -> Result<(), String> {
+    let data = ctx.data.read().await;
+    let database_pool = data.get::<DatabasePoolKey>().unwrap();
+
+    let name = get_option(options, "name").and_then(|value| value.as_str()).unwrap();
+    let shared = get_option(options, "shared").and_then(|value| value.as_bool()).unwrap_or(true);
+
+    if shared {
+        let channel = match get_channel_by_id(database_pool.clone(), cmd.channel_id).await {
+            Ok(channel) => channel,
+            Err(_) => return Err("Presets can only be shared in conlang channels".to_string())
+        };
+        if !channel.check_permission(&cmd.user, &cmd.member) {
+            return Err("You can only share presets in your own channel".to_string());
+        }
+    }
+
+    match share_preset(database_pool.clone(), cmd.user.id, name, shared.then_some(cmd.channel_id)).await {
+        Ok(true) if shared => {
+            let _ = cmd.edit_response(&ctx, EditInteractionResponse::new()
+                .content(format!("Preset {name} is now shared in <#{}>", cmd.channel_id))).await;
+            Ok(())
+        }
+        Ok(true) => {
+            let _ = cmd.edit_response(&ctx, EditInteractionResponse::new()
+                .content(format!("Preset {name} is no longer shared"))).await;
+            Ok(())
+        }
+        Ok(false) => Err(format!("You don't have a preset named {name}")),
+        Err(Error::Database(err)) if err.is_unique_violation() => Err(format!("A preset named {name} is already shared here")),
+        Err(err) => Err(format!("Failed to share preset: {err}"))
+    }
+}
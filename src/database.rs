@@ -3,6 +3,11 @@ use serenity::all::{ChannelId, UserId};
 use sqlx::{query, query_as, Error, FromRow, MySqlPool, Row};
 use sqlx::mysql::MySqlRow;
 use crate::channel::ConChannel;
+use crate::language::Language;
+use crate::lexicon::LexiconEntry;
+use crate::morphology::SavedParadigm;
+use crate::phonology::Inventory;
+use crate::preset::WordgenPreset;
 
 pub type SqlPool = MySqlPool;
 pub type SqlRow = MySqlRow;
@@ -18,6 +23,75 @@ impl FromRow<'_, SqlRow> for ConChannel {
     }
 }
 
+impl FromRow<'_, SqlRow> for WordgenPreset {
+    fn from_row(row: &SqlRow) -> SqlResult<WordgenPreset> {
+        let owner = UserId::new(row.get(0));
+        let name = row.get(1);
+        let channel = row.get(2);
+        let definition = row.get(3);
+
+        Ok(WordgenPreset { owner, name, channel, definition })
+    }
+}
+
+impl FromRow<'_, SqlRow> for LexiconEntry {
+    fn from_row(row: &SqlRow) -> SqlResult<LexiconEntry> {
+        let id = row.get(0);
+        let channel = ChannelId::new(row.get(1));
+        let headword = row.get(2);
+        let pronunciation = row.get(3);
+        let part_of_speech = row.get(4);
+        let glosses = row.get(5);
+        let notes = row.get(6);
+        let created = row.get(7);
+        let updated = row.get(8);
+        let paradigm = row.get(9);
+
+        Ok(LexiconEntry { id, channel, headword, pronunciation, part_of_speech, glosses, notes, paradigm, created, updated })
+    }
+}
+
+impl FromRow<'_, SqlRow> for SavedParadigm {
+    fn from_row(row: &SqlRow) -> SqlResult<SavedParadigm> {
+        let channel = ChannelId::new(row.get(0));
+        let name = row.get(1);
+        let definition = row.get(2);
+
+        Ok(SavedParadigm { channel, name, definition })
+    }
+}
+
+impl FromRow<'_, SqlRow> for Language {
+    fn from_row(row: &SqlRow) -> SqlResult<Language> {
+        let channel = ChannelId::new(row.get(0));
+        let name = row.get(1);
+        let autonym = row.get(2);
+        let kind = row.get(3);
+        let word_order = row.get(4);
+        let alignment = row.get(5);
+        let morphology = row.get(6);
+        let status = row.get(7);
+        let description = row.get(8);
+        let updated = row.get(9);
+
+        Ok(Language { channel, name, autonym, kind, word_order, alignment, morphology, status, description, updated })
+    }
+}
+
+impl FromRow<'_, SqlRow> for Inventory {
+    fn from_row(row: &SqlRow) -> SqlResult<Inventory> {
+        let channel = ChannelId::new(row.get(0));
+        let consonants: String = row.get(1);
+        let vowels: String = row.get(2);
+
+        Ok(Inventory {
+            channel,
+            consonants: consonants.split_whitespace().map(str::to_string).collect(),
+            vowels: vowels.split_whitespace().map(str::to_string).collect()
+        })
+    }
+}
+
 pub async fn add_channel(pool: Arc<SqlPool>, channel: ConChannel) -> SqlResult<()> {
     query("INSERT INTO Channels (ID, Owner, Category) VALUES (?, ?, ?)")
         .bind(channel.id.get())
@@ -54,9 +128,322 @@ pub async fn get_channels_by_owner(pool: Arc<SqlPool>, id: UserId) -> SqlResult<
         .await
 }
 
-pub async fn delete_channel_by_id(pool: Arc<SqlPool>, id: ChannelId) -> SqlResult<ConChannel> {
-    query_as("DELETE FROM Channels WHERE ID = ?")
-        .bind(id.get())
+/// Deletes the channel together with its lexicon, paradigms, phoneme inventory and language profile, and stops
+/// sharing presets in it.
+pub async fn delete_channel_by_id(pool: Arc<SqlPool>, id: ChannelId) -> SqlResult<()> {
+    let mut transaction = pool.begin().await?;
+    for statement in [
+        "DELETE FROM Lexicon WHERE Channel = ?",
+        "DELETE FROM Paradigms WHERE Channel = ?",
+        "DELETE FROM Inventories WHERE Channel = ?",
+        "DELETE FROM Languages WHERE Channel = ?",
+        "UPDATE WordgenPresets SET Channel = NULL WHERE Channel = ?",
+        "DELETE FROM Channels WHERE ID = ?"
+    ] {
+        query(statement)
+            .bind(id.get())
+            .execute(&mut *transaction)
+            .await?;
+    }
+    transaction.commit().await
+}
+
+pub async fn save_preset(pool: Arc<SqlPool>, preset: WordgenPreset) -> SqlResult<()> {
+    query("INSERT INTO WordgenPresets (Owner, Name, Channel, Definition) VALUES (?, ?, ?, ?) \
+            ON DUPLICATE KEY UPDATE Definition = VALUES(Definition)")
+        .bind(preset.owner.get())
+        .bind(preset.name)
+        .bind(preset.channel)
+        .bind(preset.definition)
+        .execute(&*pool)
+        .await?;
+
+    Ok(())
+}
+
+pub async fn share_preset(pool: Arc<SqlPool>, owner: UserId, name: &str, channel: Option<ChannelId>) -> SqlResult<bool> {
+    let result = query("UPDATE WordgenPresets SET Channel = ? WHERE Owner = ? AND Name = ?")
+        .bind(channel.map(|channel| channel.get()))
+        .bind(owner.get())
+        .bind(name)
+        .execute(&*pool)
+        .await?;
+
+    Ok(result.rows_affected() > 0)
+}
+
+pub async fn get_preset(pool: Arc<SqlPool>, owner: UserId, name: &str) -> SqlResult<WordgenPreset> {
+    query_as("SELECT * FROM WordgenPresets WHERE Owner = ? AND Name = ?")
+        .bind(owner.get())
+        .bind(name)
+        .fetch_one(&*pool)
+        .await
+}
+
+pub async fn get_shared_preset(pool: Arc<SqlPool>, channel: ChannelId, name: &str) -> SqlResult<WordgenPreset> {
+    query_as("SELECT * FROM WordgenPresets WHERE Channel = ? AND Name = ?")
+        .bind(channel.get())
+        .bind(name)
+        .fetch_one(&*pool)
+        .await
+}
+
+pub async fn get_presets_by_owner(pool: Arc<SqlPool>, owner: UserId) -> SqlResult<Vec<WordgenPreset>> {
+    query_as("SELECT * FROM WordgenPresets WHERE Owner = ? ORDER BY Name")
+        .bind(owner.get())
+        .fetch_all(&*pool)
+        .await
+}
+
+pub async fn get_presets_by_channel(pool: Arc<SqlPool>, channel: ChannelId) -> SqlResult<Vec<WordgenPreset>> {
+    query_as("SELECT * FROM WordgenPresets WHERE Channel = ? ORDER BY Name")
+        .bind(channel.get())
+        .fetch_all(&*pool)
+        .await
+}
+
+pub async fn delete_preset(pool: Arc<SqlPool>, owner: UserId, name: &str) -> SqlResult<bool> {
+    let result = query("DELETE FROM WordgenPresets WHERE Owner = ? AND Name = ?")
+        .bind(owner.get())
+        .bind(name)
+        .execute(&*pool)
+        .await?;
+
+    Ok(result.rows_affected() > 0)
+}
+
+pub async fn add_word(pool: Arc<SqlPool>, entry: LexiconEntry) -> SqlResult<u64> {
+    let result = query("INSERT INTO Lexicon (Channel, Headword, Pronunciation, PartOfSpeech, Glosses, Notes, Created, Updated, Paradigm) \
+            VALUES (?, ?, ?, ?, ?, ?, UNIX_TIMESTAMP(), UNIX_TIMESTAMP(), ?)")
+        .bind(entry.channel.get())
+        .bind(entry.headword)
+        .bind(entry.pronunciation)
+        .bind(entry.part_of_speech)
+        .bind(entry.glosses)
+        .bind(entry.notes)
+        .bind(entry.paradigm)
+        .execute(&*pool)
+        .await?;
+
+    Ok(result.last_insert_id())
+}
+
+pub async fn update_word(pool: Arc<SqlPool>, entry: LexiconEntry) -> SqlResult<bool> {
+    let result = query("UPDATE Lexicon SET Headword = ?, Pronunciation = ?, PartOfSpeech = ?, Glosses = ?, Notes = ?, \
+            Paradigm = ?, Updated = UNIX_TIMESTAMP() WHERE ID = ? AND Channel = ?")
+        .bind(entry.headword)
+        .bind(entry.pronunciation)
+        .bind(entry.part_of_speech)
+        .bind(entry.glosses)
+        .bind(entry.notes)
+        .bind(entry.paradigm)
+        .bind(entry.id)
+        .bind(entry.channel.get())
+        .execute(&*pool)
+        .await?;
+
+    Ok(result.rows_affected() > 0)
+}
+
+pub async fn delete_word(pool: Arc<SqlPool>, channel: ChannelId, id: u64) -> SqlResult<bool> {
+    let result = query("DELETE FROM Lexicon WHERE ID = ? AND Channel = ?")
+        .bind(id)
+        .bind(channel.get())
+        .execute(&*pool)
+        .await?;
+
+    Ok(result.rows_affected() > 0)
+}
+
+pub async fn get_words_by_headword(pool: Arc<SqlPool>, channel: ChannelId, headword: &str) -> SqlResult<Vec<LexiconEntry>> {
+    query_as("SELECT * FROM Lexicon WHERE Channel = ? AND Headword = ? ORDER BY ID")
+        .bind(channel.get())
+        .bind(headword)
+        .fetch_all(&*pool)
+        .await
+}
+
+pub async fn get_words(pool: Arc<SqlPool>, channel: ChannelId, offset: u64, limit: u64) -> SqlResult<Vec<LexiconEntry>> {
+    query_as("SELECT * FROM Lexicon WHERE Channel = ? ORDER BY Headword, ID LIMIT ? OFFSET ?")
+        .bind(channel.get())
+        .bind(limit)
+        .bind(offset)
+        .fetch_all(&*pool)
+        .await
+}
+
+pub async fn count_words(pool: Arc<SqlPool>, channel: ChannelId) -> SqlResult<u64> {
+    let row = query("SELECT COUNT(*) FROM Lexicon WHERE Channel = ?")
+        .bind(channel.get())
         .fetch_one(&*pool)
+        .await?;
+
+    Ok(row.get::<i64, _>(0) as u64)
+}
+
+pub async fn get_lexicon(pool: Arc<SqlPool>, channel: ChannelId) -> SqlResult<Vec<LexiconEntry>> {
+    query_as("SELECT * FROM Lexicon WHERE Channel = ? ORDER BY Headword, ID")
+        .bind(channel.get())
+        .fetch_all(&*pool)
+        .await
+}
+
+/// `?, ?, ?` for an `IN (...)` list of `count` values.
+fn placeholders(count: usize) -> String {
+    vec!["?"; count].join(", ")
+}
+
+/// Words of the given channels with headwords of at least `min_length` characters.
+pub async fn search_headwords(pool: Arc<SqlPool>, channels: &[ChannelId], min_length: usize) -> SqlResult<Vec<LexiconEntry>> {
+    if channels.is_empty() {
+        return Ok(Vec::new());
+    }
+    let sql = format!("SELECT * FROM Lexicon WHERE Channel IN ({}) AND CHAR_LENGTH(Headword) >= ? ORDER BY Headword, ID",
+        placeholders(channels.len()));
+    let mut search = query_as(&sql);
+    for channel in channels {
+        search = search.bind(channel.get());
+    }
+    search.bind(min_length as u64)
+        .fetch_all(&*pool)
         .await
-}
\ No newline at end of file
+}
+
+/// Words of the given channels with a gloss containing `text`.
+pub async fn search_glosses(pool: Arc<SqlPool>, channels: &[ChannelId], text: &str) -> SqlResult<Vec<LexiconEntry>> {
+    if channels.is_empty() {
+        return Ok(Vec::new());
+    }
+    let pattern = text.replace('\\', "\\\\").replace('%', "\\%").replace('_', "\\_");
+    let sql = format!("SELECT * FROM Lexicon WHERE Channel IN ({}) AND Glosses LIKE ? ORDER BY Headword, ID",
+        placeholders(channels.len()));
+    let mut search = query_as(&sql);
+    for channel in channels {
+        search = search.bind(channel.get());
+    }
+    search.bind(format!("%{pattern}%"))
+        .fetch_all(&*pool)
+        .await
+}
+
+/// Adds the entries without an ID and updates the others, all or nothing.
+pub async fn import_words(pool: Arc<SqlPool>, entries: Vec<LexiconEntry>) -> SqlResult<()> {
+    let mut transaction = pool.begin().await?;
+    for entry in entries {
+        if entry.id == 0 {
+            query("INSERT INTO Lexicon (Channel, Headword, Pronunciation, PartOfSpeech, Glosses, Notes, Created, Updated) \
+                    VALUES (?, ?, ?, ?, ?, ?, UNIX_TIMESTAMP(), UNIX_TIMESTAMP())")
+                .bind(entry.channel.get())
+                .bind(entry.headword)
+                .bind(entry.pronunciation)
+                .bind(entry.part_of_speech)
+                .bind(entry.glosses)
+                .bind(entry.notes)
+                .execute(&mut *transaction)
+                .await?;
+        } else {
+            query("UPDATE Lexicon SET Pronunciation = ?, PartOfSpeech = ?, Glosses = ?, Notes = ?, Updated = UNIX_TIMESTAMP() \
+                    WHERE ID = ? AND Channel = ?")
+                .bind(entry.pronunciation)
+                .bind(entry.part_of_speech)
+                .bind(entry.glosses)
+                .bind(entry.notes)
+                .bind(entry.id)
+                .bind(entry.channel.get())
+                .execute(&mut *transaction)
+                .await?;
+        }
+    }
+    transaction.commit().await
+}
+
+pub async fn save_paradigm(pool: Arc<SqlPool>, paradigm: SavedParadigm) -> SqlResult<()> {
+    query("INSERT INTO Paradigms (Channel, Name, Definition) VALUES (?, ?, ?) ON DUPLICATE KEY UPDATE Definition = VALUES(Definition)")
+        .bind(paradigm.channel.get())
+        .bind(paradigm.name)
+        .bind(paradigm.definition)
+        .execute(&*pool)
+        .await?;
+
+    Ok(())
+}
+
+pub async fn get_paradigm(pool: Arc<SqlPool>, channel: ChannelId, name: &str) -> SqlResult<SavedParadigm> {
+    query_as("SELECT * FROM Paradigms WHERE Channel = ? AND Name = ?")
+        .bind(channel.get())
+        .bind(name)
+        .fetch_one(&*pool)
+        .await
+}
+
+pub async fn get_paradigms(pool: Arc<SqlPool>, channel: ChannelId) -> SqlResult<Vec<SavedParadigm>> {
+    query_as("SELECT * FROM Paradigms WHERE Channel = ? ORDER BY Name")
+        .bind(channel.get())
+        .fetch_all(&*pool)
+        .await
+}
+
+pub async fn delete_paradigm(pool: Arc<SqlPool>, channel: ChannelId, name: &str) -> SqlResult<bool> {
+    let result = query("DELETE FROM Paradigms WHERE Channel = ? AND Name = ?")
+        .bind(channel.get())
+        .bind(name)
+        .execute(&*pool)
+        .await?;
+
+    Ok(result.rows_affected() > 0)
+}
+
+pub async fn save_inventory(pool: Arc<SqlPool>, inventory: Inventory) -> SqlResult<()> {
+    query("INSERT INTO Inventories (Channel, Consonants, Vowels) VALUES (?, ?, ?) \
+            ON DUPLICATE KEY UPDATE Consonants = VALUES(Consonants), Vowels = VALUES(Vowels)")
+        .bind(inventory.channel.get())
+        .bind(inventory.consonants.join(" "))
+        .bind(inventory.vowels.join(" "))
+        .execute(&*pool)
+        .await?;
+
+    Ok(())
+}
+
+pub async fn get_inventory(pool: Arc<SqlPool>, channel: ChannelId) -> SqlResult<Inventory> {
+    query_as("SELECT * FROM Inventories WHERE Channel = ?")
+        .bind(channel.get())
+        .fetch_one(&*pool)
+        .await
+}
+
+pub async fn delete_inventory(pool: Arc<SqlPool>, channel: ChannelId) -> SqlResult<bool> {
+    let result = query("DELETE FROM Inventories WHERE Channel = ?")
+        .bind(channel.get())
+        .execute(&*pool)
+        .await?;
+
+    Ok(result.rows_affected() > 0)
+}
+
+pub async fn save_language(pool: Arc<SqlPool>, language: Language) -> SqlResult<()> {
+    query("INSERT INTO Languages (Channel, Name, Autonym, Type, WordOrder, Alignment, Morphology, Status, Description, Updated) \
+            VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, UNIX_TIMESTAMP()) ON DUPLICATE KEY UPDATE Name = VALUES(Name), Autonym = VALUES(Autonym), \
+            Type = VALUES(Type), WordOrder = VALUES(WordOrder), Alignment = VALUES(Alignment), Morphology = VALUES(Morphology), \
+            Status = VALUES(Status), Description = VALUES(Description), Updated = VALUES(Updated)")
+        .bind(language.channel.get())
+        .bind(language.name)
+        .bind(language.autonym)
+        .bind(language.kind)
+        .bind(language.word_order)
+        .bind(language.alignment)
+        .bind(language.morphology)
+        .bind(language.status)
+        .bind(language.description)
+        .execute(&*pool)
+        .await?;
+
+    Ok(())
+}
+
+pub async fn get_language(pool: Arc<SqlPool>, channel: ChannelId) -> SqlResult<Language> {
+    query_as("SELECT * FROM Languages WHERE Channel = ?")
+        .bind(channel.get())
+        .fetch_one(&*pool)
+        .await
+}
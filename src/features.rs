@@ -0,0 +1,327 @@
+use crate::phonology::{classify, ipa_symbols, Backness, Height, Inventory, Manner, Place, Segment, MISSING_INVENTORY};
+
+/// Distinctive features in the style of Hayes (2009). Features that do not apply to a segment, like anterior for
+/// velars, are left unspecified and match neither `+` nor `-`.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum Feature {
+    Syllabic,
+    Consonantal,
+    Sonorant,
+    Continuant,
+    DelayedRelease,
+    Trill,
+    Tap,
+    Click,
+    Nasal,
+    Voice,
+    SpreadGlottis,
+    ConstrictedGlottis,
+    Lateral,
+    Strident,
+    Labial,
+    Labiodental,
+    Round,
+    Coronal,
+    Anterior,
+    Distributed,
+    Dorsal,
+    High,
+    Low,
+    Front,
+    Back,
+    Tense,
+    Long
+}
+
+const FEATURES: [(Feature, &str, &[&str]); 27] = [
+    (Feature::Syllabic, "syllabic", &["syl"]),
+    (Feature::Consonantal, "consonantal", &["cons"]),
+    (Feature::Sonorant, "sonorant", &["son"]),
+    (Feature::Continuant, "continuant", &["cont"]),
+    (Feature::DelayedRelease, "delayed-release", &["delrel"]),
+    (Feature::Trill, "trill", &[]),
+    (Feature::Tap, "tap", &[]),
+    (Feature::Click, "click", &[]),
+    (Feature::Nasal, "nasal", &["nas"]),
+    (Feature::Voice, "voice", &["voi", "voiced"]),
+    (Feature::SpreadGlottis, "spread-glottis", &["sg", "spread"]),
+    (Feature::ConstrictedGlottis, "constricted-glottis", &["cg", "constricted"]),
+    (Feature::Lateral, "lateral", &["lat"]),
+    (Feature::Strident, "strident", &["strid"]),
+    (Feature::Labial, "labial", &["lab"]),
+    (Feature::Labiodental, "labiodental", &[]),
+    (Feature::Round, "round", &["rnd", "rounded"]),
+    (Feature::Coronal, "coronal", &["cor"]),
+    (Feature::Anterior, "anterior", &["ant"]),
+    (Feature::Distributed, "distributed", &["dist"]),
+    (Feature::Dorsal, "dorsal", &["dor"]),
+    (Feature::High, "high", &["hi"]),
+    (Feature::Low, "low", &["lo"]),
+    (Feature::Front, "front", &[]),
+    (Feature::Back, "back", &[]),
+    (Feature::Tense, "tense", &[]),
+    (Feature::Long, "long", &[])
+];
+
+type Values = [Option<bool>; FEATURES.len()];
+
+/// The feature values of an IPA symbol, including what diacritics like `ʰ`, `ʼ`, `ʷ`, `ː` or a tilde add.
+fn values(symbol: &str) -> Option<Values> {
+    let segment = classify(symbol)?;
+    let mut values = [None; FEATURES.len()];
+    let mut set = |feature: Feature, value: bool| values[feature as usize] = Some(value);
+
+    let nasalized = symbol.contains('\u{303}');
+    set(Feature::Long, symbol.contains('ː'));
+    set(Feature::SpreadGlottis, symbol.contains(['ʰ', 'ʱ', 'h', 'ɦ']));
+    set(Feature::ConstrictedGlottis, symbol.contains(['ʼ', 'ʔ']));
+    set(Feature::Lateral, symbol.contains(['ɬ', 'ɮ', 'l', 'ɫ', 'ɭ', 'ʎ', 'ʟ', 'ɺ', 'ǁ']));
+
+    match segment {
+        Segment::Consonant { place, manner, voiced } => {
+            let labialized = symbol.contains('ʷ') || place == Place::LabialVelar || symbol.contains('ɥ');
+            let coronal = matches!(place, Place::Dental | Place::Alveolar | Place::Postalveolar | Place::Retroflex);
+            let alveolopalatal = symbol.contains(['ɕ', 'ʑ']);
+            let dorsal = matches!(place, Place::Palatal | Place::Velar | Place::LabialVelar | Place::Uvular) || alveolopalatal;
+            let sonorant = matches!(manner, Manner::Nasal | Manner::Trill | Manner::Tap | Manner::Approximant | Manner::LateralApproximant);
+
+            set(Feature::Syllabic, symbol.contains(['\u{329}', '\u{30D}']));
+            set(Feature::Consonantal, manner != Manner::Approximant && place != Place::Glottal);
+            set(Feature::Sonorant, sonorant);
+            set(Feature::Continuant, !matches!(manner, Manner::Plosive | Manner::Affricate | Manner::Nasal | Manner::Implosive | Manner::Click));
+            set(Feature::DelayedRelease, matches!(manner, Manner::Affricate | Manner::Fricative | Manner::LateralFricative));
+            set(Feature::Trill, manner == Manner::Trill);
+            set(Feature::Tap, manner == Manner::Tap);
+            set(Feature::Click, manner == Manner::Click);
+            set(Feature::Nasal, manner == Manner::Nasal || nasalized);
+            set(Feature::Voice, voiced);
+            if manner == Manner::Implosive {
+                set(Feature::ConstrictedGlottis, true);
+            }
+            set(Feature::Strident, matches!(manner, Manner::Fricative | Manner::Affricate)
+                && matches!(place, Place::Labiodental | Place::Alveolar | Place::Postalveolar | Place::Retroflex | Place::Uvular));
+            set(Feature::Labial, matches!(place, Place::Bilabial | Place::Labiodental) || labialized);
+            if matches!(place, Place::Bilabial | Place::Labiodental) {
+                set(Feature::Labiodental, place == Place::Labiodental);
+            }
+            set(Feature::Round, labialized);
+            set(Feature::Coronal, coronal);
+            if coronal {
+                set(Feature::Anterior, matches!(place, Place::Dental | Place::Alveolar));
+                set(Feature::Distributed, matches!(place, Place::Dental | Place::Postalveolar));
+            }
+            set(Feature::Dorsal, dorsal);
+            if dorsal {
+                set(Feature::High, place != Place::Uvular);
+                set(Feature::Low, false);
+                set(Feature::Front, place == Place::Palatal || alveolopalatal);
+                set(Feature::Back, !(place == Place::Palatal || alveolopalatal));
+            } else if place == Place::Pharyngeal {
+                set(Feature::High, false);
+                set(Feature::Low, true);
+            }
+        }
+        Segment::Vowel { height, backness, rounded } => {
+            set(Feature::Syllabic, !symbol.contains('\u{32F}'));
+            set(Feature::Consonantal, false);
+            set(Feature::Sonorant, true);
+            set(Feature::Continuant, true);
+            set(Feature::Trill, false);
+            set(Feature::Tap, false);
+            set(Feature::Click, false);
+            set(Feature::Nasal, nasalized);
+            set(Feature::Voice, !symbol.contains(['\u{325}', '\u{30A}']));
+            set(Feature::Strident, false);
+            set(Feature::Labial, rounded);
+            set(Feature::Round, rounded);
+            set(Feature::Coronal, false);
+            set(Feature::Dorsal, true);
+            set(Feature::High, matches!(height, Height::Close | Height::NearClose));
+            set(Feature::Low, matches!(height, Height::NearOpen | Height::Open));
+            set(Feature::Front, backness == Backness::Front);
+            set(Feature::Back, backness == Backness::Back);
+            set(Feature::Tense, matches!(height, Height::Close | Height::CloseMid | Height::Open));
+        }
+    }
+    Some(values)
+}
+
+/// A set of sounds described by features, like `[+voice -continuant]` or `[+labial]`. Every feature needs a sign, so
+/// brackets like `[hi]` stay literal letters in wordgen patterns. With a leading `@`, only sounds of the channel's
+/// phoneme inventory are included.
+pub struct NaturalClass {
+    inventory: bool,
+    terms: Vec<(Feature, bool)>
+}
+
+impl NaturalClass {
+    /// Returns `None` for brackets that are not a feature expression, like the alternation `[s|ʃ]` or the literal `[hi]`
+    /// of wordgen.
+    pub fn parse(text: &str) -> Result<Option<Self>, String> {
+        let (inventory, text) = match text.strip_prefix('@') {
+            Some(text) => (true, text),
+            None => (false, text)
+        };
+        let Some(content) = text.strip_prefix('[').and_then(|text| text.strip_suffix(']')) else {
+            return Ok(None);
+        };
+        if content.contains(['|', '[']) || content.trim().is_empty() {
+            return Ok(None);
+        }
+
+        let mut terms = Vec::new();
+        for term in content.split_whitespace() {
+            let (value, name) = match (term.strip_prefix('+'), term.strip_prefix(['-', '−'])) {
+                (Some(name), _) => (true, name),
+                (_, Some(name)) => (false, name),
+                _ => return Ok(None)
+            };
+            match FEATURES.iter().find(|(_, feature, aliases)| *feature == name || aliases.contains(&name)) {
+                Some((feature, _, _)) => terms.push((*feature, value)),
+                // Only something that reads like a feature name is a typo, `[-]` is just a hyphen
+                None if name.chars().count() > 1 && name.chars().all(|char| char.is_alphabetic() || char == '-') => {
+                    return Err(format!("Unknown feature: {name}. Features are {}",
+                        FEATURES.iter().map(|(_, name, _)| *name).collect::<Vec<_>>().join(", ")));
+                }
+                None => return Ok(None)
+            }
+        }
+        Ok(Some(NaturalClass { inventory, terms }))
+    }
+
+    /// A name for the class that can be used as a category name, like `+voice-continuant`.
+    pub fn name(&self) -> String {
+        let terms = self.terms.iter()
+            .map(|(feature, value)| format!("{}{}", if *value { '+' } else { '-' }, FEATURES[*feature as usize].1))
+            .collect::<String>();
+        match self.inventory {
+            true => format!("@{terms}"),
+            false => terms
+        }
+    }
+
+    fn matches(&self, values: &Values) -> bool {
+        self.terms.iter().all(|(feature, value)| values[*feature as usize] == Some(*value))
+    }
+
+    fn candidates<'a>(&self, inventory: Option<&'a Inventory>) -> Result<Vec<&'a str>, String> {
+        if !self.inventory {
+            return Ok(ipa_symbols().collect());
+        }
+        let inventory = inventory.ok_or(MISSING_INVENTORY)?;
+        Ok(inventory.consonants.iter().chain(&inventory.vowels).map(String::as_str).collect())
+    }
+
+    /// The sounds with all features of the class. Symbols of the IPA chart with the same features as an earlier one,
+    /// like `ɫ` and `l`, are left out.
+    pub fn members<'a>(&self, inventory: Option<&'a Inventory>) -> Result<Vec<&'a str>, String> {
+        let mut seen = Vec::new();
+        let mut members = Vec::new();
+        for symbol in self.candidates(inventory)? {
+            let Some(values) = values(symbol) else {
+                continue;
+            };
+            if self.matches(&values) && (self.inventory || !seen.contains(&values)) {
+                members.push(symbol);
+            }
+            seen.push(values);
+        }
+        Ok(members)
+    }
+
+    /// The sound with the features of the class that is otherwise closest to `symbol`, or `symbol` itself if no sound
+    /// of the same kind has them. Like in most feature systems, `[-continuant]` turns `s` into `t` even though `t` is
+    /// also not strident.
+    pub fn change<'a>(&self, symbol: &'a str, inventory: Option<&'a Inventory>) -> Result<&'a str, String> {
+        let Some(current) = values(symbol) else {
+            return Ok(symbol);
+        };
+        let mut target = current;
+        for (feature, value) in &self.terms {
+            target[*feature as usize] = Some(*value);
+        }
+
+        let kept = [Feature::Syllabic, Feature::Consonantal];
+        Ok(self.candidates(inventory)?.into_iter()
+            .filter_map(|candidate| values(candidate).map(|values| (candidate, values)))
+            .filter(|(_, values)| self.matches(values) && kept.iter().all(|feature| values[*feature as usize] == target[*feature as usize]))
+            .min_by_key(|(_, values)| values.iter().zip(&target).filter(|(value, target)| value != target).count())
+            .map_or(symbol, |(candidate, _)| candidate))
+    }
+}
+
+/// The feature expressions in `text` with their byte ranges, skipping brackets that are something else.
+pub fn find_classes(text: &str) -> Result<Vec<(usize, usize, NaturalClass)>, String> {
+    let mut classes = Vec::new();
+    let mut from = 0;
+    while let Some(open) = text[from..].find('[').map(|open| from + open) {
+        let Some(close) = text[open..].find(']').map(|close| open + close + 1) else {
+            break;
+        };
+        let start = match text[..open].ends_with('@') {
+            true => open - 1,
+            false => open
+        };
+        match NaturalClass::parse(&text[start..close])? {
+            Some(class) => {
+                classes.push((start, close, class));
+                from = close;
+            }
+            None => from = open + 1
+        }
+    }
+    Ok(classes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn class(text: &str) -> NaturalClass {
+        NaturalClass::parse(text).unwrap().unwrap_or_else(|| panic!("{text} should be a natural class"))
+    }
+
+    #[test]
+    fn brackets_without_signs_stay_literal() {
+        for text in ["[hi]", "[lo]", "[front]", "[nasal]", "[tap]", "[s|ʃ]", "[a]", "[-]", "[+voice labial]"] {
+            assert!(NaturalClass::parse(text).unwrap().is_none(), "{text} should not be a natural class");
+        }
+        assert!(find_classes("C[hi]V[s|ʃ]").unwrap().is_empty());
+    }
+
+    #[test]
+    fn signed_features_are_classes() {
+        let high = class("[+hi -back]");
+        assert_eq!(high.name(), "+high-back");
+        let members = high.members(None).unwrap();
+        assert!(members.contains(&"i") && members.contains(&"y"), "{members:?}");
+        assert!(!members.contains(&"u") && !members.contains(&"a"), "{members:?}");
+
+        let classes = find_classes("C[+hi -back]@[−nasal]").unwrap();
+        assert_eq!(classes.iter().map(|(start, end, _)| (*start, *end)).collect::<Vec<_>>(), [(1, 12), (12, 23)]);
+        assert_eq!(classes[1].2.name(), "@-nasal");
+    }
+
+    #[test]
+    fn unknown_features_are_errors() {
+        assert!(NaturalClass::parse("[+voise]").is_err());
+        assert!(NaturalClass::parse("[+voice -contnuant]").is_err());
+    }
+
+    #[test]
+    fn changes_pick_the_closest_sound() {
+        assert_eq!(class("[+voice]").change("p", None).unwrap(), "b");
+        assert_eq!(class("[-voice]").change("z", None).unwrap(), "s");
+        assert_eq!(class("[-continuant]").change("s", None).unwrap(), "t");
+        assert_eq!(class("[+round]").change("i", None).unwrap(), "y");
+        assert_eq!(class("[+syllabic]").change("k", None).unwrap(), "k");
+    }
+
+    #[test]
+    fn inventory_classes_need_an_inventory() {
+        let inventory = Inventory::parse(serenity::all::ChannelId::new(1), "p t k m n", "a i u").unwrap();
+        assert_eq!(class("@[+nasal]").members(Some(&inventory)).unwrap(), ["m", "n"]);
+        assert_eq!(class("@[+voice]").change("p", Some(&inventory)).unwrap(), "m");
+        assert!(class("@[+nasal]").members(None).is_err());
+    }
+}
@@ -33,11 +33,25 @@ impl EventHandler for Handler {
                     crate::commands::delete::register(),
                     crate::commands::edit::register(),
                     crate::commands::fixperms::register(),
+                    crate::commands::gloss::register(),
+                    crate::commands::inflect::register(),
+                    crate::commands::ipa::register(),
+                    crate::commands::ipa_message::register(),
+                    crate::commands::lang::register(profile),
                     crate::commands::migrate::register(),
                     crate::commands::mode::register(),
+                    crate::commands::paradigm::register(),
+                    crate::commands::phonology::register(),
+                    crate::commands::romanize::register(),
+                    crate::commands::search::register(),
+                    crate::commands::soundchange::register(),
                     crate::commands::unban::register(),
                     crate::commands::viewer::register(),
+                    crate::commands::word::register(),
                     crate::commands::wordgen::register(),
+                    crate::commands::wordgen_editor::register(),
+                    crate::commands::wordgen_import::register(),
+                    crate::commands::wordgen_preset::register(),
                 ]).await.expect("Failed to set guild commands");
 
                 Command::create_global_command(&ctx, crate::commands::debug::register()).await.expect("Failed to set global command: debug");
@@ -68,11 +82,25 @@ impl EventHandler for Handler {
                     "delete" => crate::commands::delete::run(&ctx, cmd).await,
                     "edit" => crate::commands::edit::run(&ctx, cmd).await,
                     "fixperms" => crate::commands::fixperms::run(&ctx, cmd).await,
+                    "gloss" => crate::commands::gloss::run(&ctx, cmd).await,
+                    "inflect" => crate::commands::inflect::run(&ctx, cmd).await,
+                    "ipa" => crate::commands::ipa::run(&ctx, cmd).await,
+                    crate::commands::ipa_message::NAME => crate::commands::ipa_message::run(&ctx, cmd).await,
+                    "lang" => crate::commands::lang::run(&ctx, cmd).await,
                     "migrate" => crate::commands::migrate::run(&ctx, cmd).await,
                     "mode" => crate::commands::mode::run(&ctx, cmd).await,
+                    "paradigm" => crate::commands::paradigm::run(&ctx, cmd).await,
+                    "phonology" => crate::commands::phonology::run(&ctx, cmd).await,
+                    "romanize" => crate::commands::romanize::run(&ctx, cmd).await,
+                    "search" => crate::commands::search::run(&ctx, cmd).await,
+                    "soundchange" => crate::commands::soundchange::run(&ctx, cmd).await,
                     "unban" => crate::commands::unban::run(&ctx, cmd).await,
                     "viewer" => crate::commands::viewer::run(&ctx, cmd).await,
+                    "word" => crate::commands::word::run(&ctx, cmd).await,
                     "wordgen" => crate::commands::wordgen::run(&ctx, cmd).await,
+                    "wordgen-editor" => crate::commands::wordgen_editor::run(&ctx, cmd).await,
+                    "wordgen-import" => crate::commands::wordgen_import::run(&ctx, cmd).await,
+                    "wordgen-preset" => crate::commands::wordgen_preset::run(&ctx, cmd).await,
                     _ => {}
                 }
             }
@@ -88,6 +116,10 @@ impl EventHandler for Handler {
                     crate::commands::create_interaction::run(&ctx, comp).await;
                 } else if id == "delete-channel" {
                     crate::commands::delete_interaction::run(&ctx, comp).await;
+                } else if id.starts_with("word-list") || id.starts_with("word-import") {
+                    crate::commands::word_interaction::run(&ctx, comp).await;
+                } else if id.starts_with("search:") {
+                    crate::commands::search_interaction::run(&ctx, comp).await;
                 }
             }
             Interaction::Modal(modal) => {
@@ -102,6 +134,10 @@ impl EventHandler for Handler {
                     crate::commands::create_modal::run(&ctx, modal).await;
                 } else if id == "edit-channel" {
                     crate::commands::edit_modal::run(&ctx, modal).await;
+                } else if id == "wordgen-editor" {
+                    crate::commands::wordgen_modal::run(&ctx, modal).await;
+                } else if id.starts_with("gloss:") {
+                    crate::commands::gloss_modal::run(&ctx, modal).await;
                 }
             }
             _ => {}
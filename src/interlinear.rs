@@ -0,0 +1,203 @@
+use unicode_width::UnicodeWidthStr;
+
+const SEPARATORS: [char; 3] = ['-', '=', '~'];
+const MAX_LINE_WIDTH: usize = 60;
+const SMALL_CAPS: [char; 26] = ['ᴀ', 'ʙ', 'ᴄ', 'ᴅ', 'ᴇ', 'ꜰ', 'ɢ', 'ʜ', 'ɪ', 'ᴊ', 'ᴋ', 'ʟ', 'ᴍ', 'ɴ', 'ᴏ', 'ᴘ', 'ǫ', 'ʀ', 'ꜱ', 'ᴛ', 'ᴜ', 'ᴠ', 'ᴡ', 'x', 'ʏ', 'ᴢ'];
+
+/// An example sentence with Leipzig-style glosses. Morphemes are separated by `-`, `=` or `~` and every morpheme
+/// needs a gloss behind the same separator. A gloss of `?` is a blank to fill in from the lexicon.
+pub struct Interlinear {
+    source: Option<Vec<String>>,
+    morphemes: Vec<String>,
+    glosses: Vec<String>
+}
+
+impl Interlinear {
+    pub fn parse(source: &str, morphemes: &str, glosses: &str) -> Result<Self, String> {
+        let morphemes: Vec<String> = morphemes.split_whitespace().map(str::to_string).collect();
+        if morphemes.is_empty() {
+            return Err("Please enter the segmented sentence. Example: kala-n tupa-s".to_string());
+        }
+
+        let glosses: Vec<String> = match glosses.trim() {
+            "" => morphemes.iter().map(|word| blanks(word)).collect(),
+            glosses => glosses.split_whitespace().map(str::to_string).collect()
+        };
+        if glosses.len() != morphemes.len() {
+            return Err(format!("The sentence has {} words, but there are {} glosses", morphemes.len(), glosses.len()));
+        }
+        for (idx, (word, gloss)) in morphemes.iter().zip(&glosses).enumerate() {
+            let word_separators: Vec<char> = word.chars().filter(|char| SEPARATORS.contains(char)).collect();
+            let gloss_separators: Vec<char> = gloss.chars().filter(|char| SEPARATORS.contains(char)).collect();
+            if word_separators.len() != gloss_separators.len() {
+                return Err(format!("Word {}: {word} has {} morphemes, but {gloss} has {} glosses", idx + 1,
+                    word_separators.len() + 1, gloss_separators.len() + 1));
+            }
+            if word_separators != gloss_separators {
+                return Err(format!("Word {}: {word} and {gloss} use different separators", idx + 1));
+            }
+        }
+
+        let source: Vec<String> = source.split_whitespace().map(str::to_string).collect();
+        let source = match source.len() {
+            0 => None,
+            count if count == morphemes.len() => Some(source),
+            count => return Err(format!("The original sentence has {count} words, but the segmented one has {}", morphemes.len()))
+        };
+
+        Ok(Interlinear { source, morphemes, glosses })
+    }
+
+    /// Fills the `?` blanks with `lookup(morpheme)` and returns the morphemes that could not be found.
+    pub fn fill(&mut self, lookup: impl Fn(&str) -> Option<String>) -> Vec<String> {
+        let mut missing = Vec::new();
+        for (word, gloss) in self.morphemes.iter().zip(self.glosses.iter_mut()) {
+            *gloss = gloss.split_inclusive(SEPARATORS).zip(word.split(SEPARATORS)).map(|(part, morpheme)| {
+                let text = part.strip_suffix(SEPARATORS).unwrap_or(part);
+                let separator = &part[text.len()..];
+                if text != "?" {
+                    return part.to_string();
+                }
+                match lookup(morpheme) {
+                    Some(found) => format!("{found}{separator}"),
+                    None => {
+                        if !missing.iter().any(|missing| missing == morpheme) {
+                            missing.push(morpheme.to_string());
+                        }
+                        part.to_string()
+                    }
+                }
+            }).collect();
+        }
+        missing
+    }
+
+    /// Aligned lines for a monospace block. Sentences wider than a phone screen continue in another block of lines.
+    pub fn render(&self, small_caps: bool) -> String {
+        let glosses: Vec<String> = match small_caps {
+            true => self.glosses.iter().map(|gloss| to_small_caps(gloss)).collect(),
+            false => self.glosses.clone()
+        };
+        let mut lines: Vec<Vec<&str>> = Vec::new();
+        if let Some(source) = &self.source {
+            lines.push(source.iter().map(String::as_str).collect());
+        }
+        lines.push(self.morphemes.iter().map(String::as_str).collect());
+        lines.push(glosses.iter().map(String::as_str).collect());
+
+        let widths: Vec<usize> = (0..self.morphemes.len())
+            .map(|idx| lines.iter().map(|line| line[idx].width()).max().unwrap_or(0))
+            .collect();
+
+        let mut groups = Vec::new();
+        let mut start = 0;
+        while start < widths.len() {
+            let mut end = start + 1;
+            let mut width = widths[start];
+            while end < widths.len() && width + 2 + widths[end] <= MAX_LINE_WIDTH {
+                width += 2 + widths[end];
+                end += 1;
+            }
+            groups.push(lines.iter().map(|line| {
+                let mut text = String::new();
+                for idx in start..end {
+                    text.push_str(line[idx]);
+                    text.push_str(&" ".repeat(widths[idx] - line[idx].width() + 2));
+                }
+                text.trim_end().to_string()
+            }).collect::<Vec<_>>().join("\n"));
+            start = end;
+        }
+        groups.join("\n\n")
+    }
+}
+
+/// `kala-n=ta` becomes `?-?=?`.
+fn blanks(word: &str) -> String {
+    word.split_inclusive(SEPARATORS)
+        .map(|part| match part.chars().last() {
+            Some(separator) if SEPARATORS.contains(&separator) => format!("?{separator}"),
+            _ => "?".to_string()
+        })
+        .collect()
+}
+
+/// Abbreviations like `NOM` or `1SG` become `ɴᴏᴍ` and `1ꜱɢ`. Glosses with lowercase letters are left alone.
+fn to_small_caps(gloss: &str) -> String {
+    gloss.split_inclusive(['.', '-', '=', '~'])
+        .map(|part| match part.chars().any(char::is_lowercase) {
+            true => part.to_string(),
+            false => part.chars().map(|char| match char {
+                'A'..='Z' => SMALL_CAPS[(char as u8 - b'A') as usize],
+                char => char
+            }).collect()
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn render(source: &str, morphemes: &str, glosses: &str) -> String {
+        Interlinear::parse(source, morphemes, glosses).unwrap().render(true)
+    }
+
+    fn parse_error(source: &str, morphemes: &str, glosses: &str) -> String {
+        match Interlinear::parse(source, morphemes, glosses) {
+            Ok(_) => panic!("{morphemes} / {glosses} should not parse"),
+            Err(err) => err
+        }
+    }
+
+    #[test]
+    fn columns_are_aligned() {
+        assert_eq!(render("", "kala-n tupa-s", "fish-ACC see-3SG"), "kala-n    tupa-s\nfish-ᴀᴄᴄ  see-3ꜱɢ");
+        assert_eq!(render("Kalan tupas.", "kala-n tupa-s", "fish-ACC see-3SG"),
+            "Kalan     tupas.\nkala-n    tupa-s\nfish-ᴀᴄᴄ  see-3ꜱɢ");
+    }
+
+    #[test]
+    fn combining_marks_take_no_space() {
+        // The á is a plain a followed by U+0301
+        assert_eq!(render("", "ta\u{301}-n ko", "sun-GEN eye"), "ta\u{301}-n     ko\nsun-ɢᴇɴ  eye");
+    }
+
+    #[test]
+    fn wide_glyphs_take_two_columns() {
+        assert_eq!(render("", "日本-ni iku", "Japan-to go"), "日本-ni   iku\nJapan-to  go");
+        assert_eq!(render("", "a-b 猫", "x-y cat"), "a-b  猫\nx-y  cat");
+    }
+
+    #[test]
+    fn long_sentences_wrap() {
+        let words = ["morpheme"; 8].join(" ");
+        let rendered = render("", &words, &words);
+        let groups: Vec<&str> = rendered.split("\n\n").collect();
+        assert_eq!(groups.len(), 2);
+        assert!(groups.iter().all(|group| group.lines().all(|line| line.width() <= MAX_LINE_WIDTH)));
+    }
+
+    #[test]
+    fn blanks_are_filled_from_the_lexicon() {
+        let mut interlinear = Interlinear::parse("", "kala-n tupa=ko", "").unwrap();
+        let missing = interlinear.fill(|morpheme| (morpheme == "kala").then(|| "fish".to_string()));
+        assert_eq!(missing, ["n", "tupa", "ko"]);
+        assert_eq!(interlinear.render(false), "kala-n  tupa=ko\nfish-?  ?=?");
+    }
+
+    #[test]
+    fn glosses_must_match_the_morphemes() {
+        assert_eq!(parse_error("", "", "x"), "Please enter the segmented sentence. Example: kala-n tupa-s");
+        assert_eq!(parse_error("", "a b", "x"), "The sentence has 2 words, but there are 1 glosses");
+        assert_eq!(parse_error("", "a-b", "x"), "Word 1: a-b has 2 morphemes, but x has 1 glosses");
+        assert_eq!(parse_error("", "a-b", "x=y"), "Word 1: a-b and x=y use different separators");
+        assert_eq!(parse_error("a b c", "a b", "x y"), "The original sentence has 3 words, but the segmented one has 2");
+    }
+
+    #[test]
+    fn small_caps_only_change_abbreviations() {
+        assert_eq!(to_small_caps("see-1SG.PST"), "see-1ꜱɢ.ᴘꜱᴛ");
+        assert_eq!(to_small_caps("New.York"), "New.York");
+    }
+}
@@ -0,0 +1,15 @@
+use serenity::all::ChannelId;
+
+/// What the owner of a conlang channel tells about their language with /lang set.
+pub struct Language {
+    pub channel: ChannelId,
+    pub name: String,
+    pub autonym: Option<String>,
+    pub kind: Option<String>,
+    pub word_order: Option<String>,
+    pub alignment: Option<String>,
+    pub morphology: Option<String>,
+    pub status: Option<String>,
+    pub description: Option<String>,
+    pub updated: i64
+}
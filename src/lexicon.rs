@@ -0,0 +1,109 @@
+use serenity::all::ChannelId;
+use unicode_normalization::char::is_combining_mark;
+use unicode_normalization::UnicodeNormalization;
+
+pub struct LexiconEntry {
+    pub id: u64,
+    pub channel: ChannelId,
+    pub headword: String,
+    pub pronunciation: Option<String>,
+    pub part_of_speech: Option<String>,
+    pub glosses: String,
+    pub notes: Option<String>,
+    pub paradigm: Option<String>,
+    pub created: i64,
+    pub updated: i64
+}
+
+impl LexiconEntry {
+    pub fn glosses(&self) -> Vec<&str> {
+        self.glosses.split(';').map(str::trim).filter(|gloss| !gloss.is_empty()).collect()
+    }
+
+    /// One line like `**kala** /ˈka.la/ *n.* fish; water (#12)`.
+    pub fn summary(&self) -> String {
+        let mut summary = format!("**{}**", self.headword);
+        if let Some(pronunciation) = &self.pronunciation {
+            summary.push_str(&format!(" /{pronunciation}/"));
+        }
+        if let Some(part_of_speech) = &self.part_of_speech {
+            summary.push_str(&format!(" *{part_of_speech}*"));
+        }
+        summary.push_str(&format!(" {} (#{})", self.glosses().join("; "), self.id));
+        summary
+    }
+
+    /// 0 if a gloss is exactly the query, 1 if a gloss contains all its words in order.
+    pub fn gloss_rank(&self, query: &str) -> Option<usize> {
+        let query = fold_words(query);
+        if query.is_empty() {
+            return None;
+        }
+        self.glosses().into_iter().filter_map(|gloss| {
+            let words = fold_words(gloss);
+            if words == query {
+                Some(0)
+            } else if words.windows(query.len()).any(|window| window == query) {
+                Some(1)
+            } else {
+                None
+            }
+        }).min()
+    }
+
+    /// 0 for the same word, 1 if the headword contains the query, higher for each typo up to a third of the query.
+    pub fn headword_rank(&self, query: &str) -> Option<usize> {
+        let query = fold(query.trim());
+        let headword = fold(&self.headword);
+        if query.is_empty() {
+            None
+        } else if headword == query {
+            Some(0)
+        } else if headword.contains(&query) {
+            Some(1)
+        } else {
+            let distance = edit_distance(&headword, &query);
+            (distance <= allowed_typos(&query)).then_some(distance + 1)
+        }
+    }
+}
+
+/// The fewest characters a headword needs for `headword_rank` to match the query, to narrow down the search in SQL.
+pub fn min_headword_length(query: &str) -> usize {
+    let query = fold(query.trim());
+    query.chars().count().saturating_sub(allowed_typos(&query))
+}
+
+fn allowed_typos(query: &str) -> usize {
+    (query.chars().count() / 3).max(1)
+}
+
+/// Lowercase without diacritics, so `Ñá` matches `na`.
+fn fold(text: &str) -> String {
+    text.nfd()
+        .filter(|char| !is_combining_mark(*char))
+        .flat_map(char::to_lowercase)
+        .collect()
+}
+
+fn fold_words(text: &str) -> Vec<String> {
+    fold(text).split(|char: char| !char.is_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .map(str::to_string)
+        .collect()
+}
+
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut row: Vec<usize> = (0..=b.len()).collect();
+    for (i, a) in a.chars().enumerate() {
+        let mut previous = row[0];
+        row[0] = i + 1;
+        for (j, b) in b.iter().enumerate() {
+            let current = row[j + 1];
+            row[j + 1] = if a == *b { previous } else { previous.min(row[j]).min(current) + 1 };
+            previous = current;
+        }
+    }
+    row[b.len()]
+}
@@ -1,5 +1,16 @@
 pub mod channel;
 pub mod profile;
+pub mod phonemes;
+pub mod orthography;
+pub mod xsampa;
+pub mod phonology;
+pub mod features;
+pub mod interlinear;
+pub mod preset;
+pub mod lexicon;
+pub mod language;
+pub mod morphology;
+pub mod wordgen;
 pub mod database;
 pub mod handler;
 pub mod commands;
@@ -48,6 +59,7 @@ async fn main() {
         .password(&sql_password))
         .await
         .expect("Failed to connect to database");
+    sqlx::migrate!().run(&database_pool).await.expect("Failed to run database migrations");
 
     {
         let mut data = client.data.write().await;
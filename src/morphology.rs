@@ -0,0 +1,332 @@
+use std::collections::HashMap;
+use serenity::all::ChannelId;
+use crate::phonemes::{category_at, parse_category_definition};
+
+/// An inflection table built from rules on the stem, such as
+///
+/// ```text
+/// rows = nominative, accusative, genitive
+/// columns = singular, plural
+/// V: a, e, i, o, u
+/// nominative.singular = ~
+/// nominative.plural = *V: +s | +es
+/// accusative.singular = *a: -a +am | +em
+/// genitive.plural = *a: a>o +rum | +um
+/// ```
+///
+/// Entries are separated by lines or `;` and numbered without the empty ones in errors, like in /paradigm show.
+/// Every cell lists alternatives separated by `|`. The first alternative whose conditions match the stem is used.
+/// Conditions like `*a` (ends with a), `k*` (starts with k) or `!*V` (does not end with a vowel) are joined with `&`
+/// and can use categories. The operations are `+x` (suffix), `x+` (prefix), `-x` and `x-` (remove a suffix or prefix),
+/// `x>y` (replace the last x), `=form` (suppletion) and `~` (the stem as it is).
+pub struct Paradigm {
+    pub rows: Vec<String>,
+    pub columns: Vec<String>,
+    cells: HashMap<(usize, usize), Vec<Alternative>>,
+    categories: HashMap<String, Vec<String>>
+}
+
+struct Alternative {
+    conditions: Vec<Condition>,
+    operations: Vec<Operation>
+}
+
+struct Condition {
+    negated: bool,
+    at_end: bool,
+    pattern: Vec<Segment>
+}
+
+enum Segment {
+    Letters(String),
+    Category(String)
+}
+
+enum Operation {
+    Suffix(String),
+    Prefix(String),
+    RemoveSuffix(String),
+    RemovePrefix(String),
+    Replace(String, String),
+    Suppletion(String),
+    Stem
+}
+
+/// A paradigm as stored for a channel.
+pub struct SavedParadigm {
+    pub channel: ChannelId,
+    pub name: String,
+    pub definition: String
+}
+
+impl Paradigm {
+    pub fn parse(definition: &str) -> Result<Self, String> {
+        let mut rows = Vec::new();
+        let mut columns = Vec::new();
+        let mut categories = HashMap::new();
+        let mut cells = Vec::new();
+
+        let entries = definition.split(['\n', ';']).map(str::trim).filter(|entry| !entry.is_empty());
+        for (idx, entry) in entries.enumerate() {
+            if entry.starts_with('#') {
+                continue;
+            }
+
+            match entry.split_once('=').map(|(key, value)| (key.trim(), value.trim())) {
+                Some(("rows", value)) => rows = split_names(value),
+                Some(("columns", value)) => columns = split_names(value),
+                Some((key, value)) => cells.push((idx + 1, key, value)),
+                None if entry.contains(':') => {
+                    let (name, category) = parse_category_definition(&format!("in entry {}", idx + 1), entry)?;
+                    categories.insert(name, category.letters.iter().map(|letter| letter.trim().to_string()).filter(|letter| !letter.is_empty()).collect());
+                }
+                None => return Err(format!("Entry {} is neither a setting, a category nor a cell: {entry}", idx + 1))
+            }
+        }
+
+        if rows.is_empty() {
+            return Err("The paradigm has no rows. Example: rows = nominative, accusative".to_string());
+        }
+        if columns.is_empty() {
+            columns.push(String::new());
+        }
+
+        let mut paradigm = Paradigm { rows, columns, cells: HashMap::new(), categories };
+        for (entry, key, value) in cells {
+            let cell = paradigm.find_cell(key).ok_or(format!("Entry {entry}: There is no cell {key}. Use row.column, like {}",
+                paradigm.cell_name(0, paradigm.columns.len() - 1)))?;
+            let alternatives = value.split('|')
+                .map(|alternative| paradigm.parse_alternative(alternative))
+                .collect::<Result<Vec<_>, _>>()
+                .map_err(|err| format!("Entry {entry}: {err}"))?;
+            paradigm.cells.insert(cell, alternatives);
+        }
+        if paradigm.cells.is_empty() {
+            return Err("The paradigm has no cells. Example: nominative.plural = +s".to_string());
+        }
+        Ok(paradigm)
+    }
+
+    fn find_cell(&self, key: &str) -> Option<(usize, usize)> {
+        let (row, column) = match key.rsplit_once('.') {
+            Some((row, column)) => (row.trim(), column.trim()),
+            None => (key, "")
+        };
+        let row = self.rows.iter().position(|name| name.eq_ignore_ascii_case(row))?;
+        let column = self.columns.iter().position(|name| name.eq_ignore_ascii_case(column))?;
+        Some((row, column))
+    }
+
+    fn cell_name(&self, row: usize, column: usize) -> String {
+        match self.columns[column].as_str() {
+            "" => self.rows[row].clone(),
+            column => format!("{}.{column}", self.rows[row])
+        }
+    }
+
+    fn parse_alternative(&self, alternative: &str) -> Result<Alternative, String> {
+        let (conditions, operations) = match alternative.split_once(':') {
+            Some((conditions, operations)) => (conditions.split('&').map(|condition| self.parse_condition(condition.trim())).collect::<Result<_, _>>()?, operations),
+            None => (Vec::new(), alternative)
+        };
+        let operations = operations.split_whitespace().map(parse_operation).collect::<Result<Vec<_>, _>>()?;
+        if operations.is_empty() {
+            return Err(format!("{} has no operations. Use ~ for the unchanged stem", alternative.trim()));
+        }
+        Ok(Alternative { conditions, operations })
+    }
+
+    fn parse_condition(&self, condition: &str) -> Result<Condition, String> {
+        let (negated, condition) = match condition.strip_prefix('!') {
+            Some(condition) => (true, condition),
+            None => (false, condition)
+        };
+        let (at_end, pattern) = match (condition.strip_prefix('*'), condition.strip_suffix('*')) {
+            (Some(pattern), None) => (true, pattern),
+            (None, Some(pattern)) => (false, pattern),
+            _ => return Err(format!("Condition {condition} needs a * before or after it, like *a for stems ending in a"))
+        };
+
+        let mut segments = Vec::new();
+        let mut rest = pattern;
+        while let Some(char) = rest.chars().next() {
+            match category_at(&self.categories, rest) {
+                Some((name, length)) => {
+                    segments.push(Segment::Category(name.to_string()));
+                    rest = &rest[length..];
+                }
+                None => {
+                    match segments.last_mut() {
+                        Some(Segment::Letters(letters)) => letters.push(char),
+                        _ => segments.push(Segment::Letters(char.to_string()))
+                    }
+                    rest = &rest[char.len_utf8()..];
+                }
+            }
+        }
+        Ok(Condition { negated, at_end, pattern: segments })
+    }
+
+    /// The forms of `stem` by row and column. Cells without a rule or a matching alternative are `None`.
+    pub fn inflect(&self, stem: &str) -> Vec<Vec<Option<String>>> {
+        (0..self.rows.len()).map(|row| (0..self.columns.len()).map(|column| {
+            let alternative = self.cells.get(&(row, column))?.iter()
+                .find(|alternative| alternative.conditions.iter().all(|condition| self.matches(condition, stem)))?;
+            let mut form = stem.to_string();
+            for operation in &alternative.operations {
+                form = operation.apply(&form);
+            }
+            Some(form)
+        }).collect()).collect()
+    }
+
+    fn matches(&self, condition: &Condition, stem: &str) -> bool {
+        let matched = if condition.at_end {
+            self.matches_end(&condition.pattern, stem)
+        } else {
+            self.matches_start(&condition.pattern, stem)
+        };
+        matched != condition.negated
+    }
+
+    fn matches_end(&self, pattern: &[Segment], text: &str) -> bool {
+        let Some((last, pattern)) = pattern.split_last() else {
+            return true;
+        };
+        match last {
+            Segment::Letters(letters) => text.strip_suffix(letters.as_str()).is_some_and(|rest| self.matches_end(pattern, rest)),
+            Segment::Category(name) => self.categories[name].iter()
+                .any(|letter| text.strip_suffix(letter.as_str()).is_some_and(|rest| self.matches_end(pattern, rest)))
+        }
+    }
+
+    fn matches_start(&self, pattern: &[Segment], text: &str) -> bool {
+        let Some((first, pattern)) = pattern.split_first() else {
+            return true;
+        };
+        match first {
+            Segment::Letters(letters) => text.strip_prefix(letters.as_str()).is_some_and(|rest| self.matches_start(pattern, rest)),
+            Segment::Category(name) => self.categories[name].iter()
+                .any(|letter| text.strip_prefix(letter.as_str()).is_some_and(|rest| self.matches_start(pattern, rest)))
+        }
+    }
+}
+
+fn split_names(value: &str) -> Vec<String> {
+    value.split(',').map(str::trim).filter(|name| !name.is_empty()).map(str::to_string).collect()
+}
+
+fn parse_operation(operation: &str) -> Result<Operation, String> {
+    if operation == "~" {
+        Ok(Operation::Stem)
+    } else if let Some(form) = operation.strip_prefix('=') {
+        Ok(Operation::Suppletion(form.to_string()))
+    } else if let Some((from, to)) = operation.split_once('>') {
+        match from.is_empty() {
+            true => Err(format!("{operation} does not say what to replace")),
+            false => Ok(Operation::Replace(from.to_string(), to.trim_start_matches('∅').to_string()))
+        }
+    } else if let Some(suffix) = operation.strip_prefix('+') {
+        Ok(Operation::Suffix(suffix.to_string()))
+    } else if let Some(prefix) = operation.strip_suffix('+') {
+        Ok(Operation::Prefix(prefix.to_string()))
+    } else if let Some(suffix) = operation.strip_prefix('-') {
+        Ok(Operation::RemoveSuffix(suffix.to_string()))
+    } else if let Some(prefix) = operation.strip_suffix('-') {
+        Ok(Operation::RemovePrefix(prefix.to_string()))
+    } else {
+        Err(format!("Unknown operation: {operation}. Use +x, x+, -x, x-, x>y, =form or ~"))
+    }
+}
+
+impl Operation {
+    fn apply(&self, form: &str) -> String {
+        match self {
+            Operation::Suffix(suffix) => format!("{form}{suffix}"),
+            Operation::Prefix(prefix) => format!("{prefix}{form}"),
+            Operation::RemoveSuffix(suffix) => form.strip_suffix(suffix.as_str()).unwrap_or(form).to_string(),
+            Operation::RemovePrefix(prefix) => form.strip_prefix(prefix.as_str()).unwrap_or(form).to_string(),
+            Operation::Replace(from, to) => match form.rfind(from.as_str()) {
+                Some(start) => format!("{}{to}{}", &form[..start], &form[start + from.len()..]),
+                None => form.to_string()
+            },
+            Operation::Suppletion(suppletion) => suppletion.clone(),
+            Operation::Stem => form.to_string()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const LATIN: &str = "rows = nominative, accusative, genitive; columns = singular, plural; V: a, e, i, o, u
+        nominative.singular = ~
+        nominative.plural = *V: +s | +es
+        accusative.singular = *a: -a +am | +em
+        genitive.plural = *a: a>o +rum | +um";
+
+    fn forms(definition: &str, stem: &str) -> Vec<Vec<Option<String>>> {
+        Paradigm::parse(definition).unwrap().inflect(stem)
+    }
+
+    fn parse_error(definition: &str) -> String {
+        match Paradigm::parse(definition) {
+            Ok(_) => panic!("{definition} should not parse"),
+            Err(err) => err
+        }
+    }
+
+    fn cells(forms: &[&[Option<&str>]]) -> Vec<Vec<Option<String>>> {
+        forms.iter().map(|row| row.iter().map(|form| form.map(str::to_string)).collect()).collect()
+    }
+
+    #[test]
+    fn parse_reads_rows_columns_and_cells() {
+        let paradigm = Paradigm::parse(LATIN).unwrap();
+        assert_eq!(paradigm.rows, ["nominative", "accusative", "genitive"]);
+        assert_eq!(paradigm.columns, ["singular", "plural"]);
+        assert_eq!(paradigm.cells.len(), 4);
+        assert_eq!(paradigm.categories["V"], ["a", "e", "i", "o", "u"]);
+
+        let single = Paradigm::parse("rows = present, past\n# A comment\npast = +d").unwrap();
+        assert_eq!(single.columns, [""]);
+        assert_eq!(single.cell_name(1, 0), "past");
+    }
+
+    #[test]
+    fn inflect_uses_the_first_matching_alternative() {
+        assert_eq!(forms(LATIN, "rosa"), cells(&[
+            &[Some("rosa"), Some("rosas")],
+            &[Some("rosam"), None],
+            &[None, Some("rosorum")]
+        ]));
+        assert_eq!(forms(LATIN, "rex"), cells(&[
+            &[Some("rex"), Some("rexes")],
+            &[Some("rexem"), None],
+            &[None, Some("rexum")]
+        ]));
+    }
+
+    #[test]
+    fn operations() {
+        let definition = "rows = a, b, c, d, e, f; a = un+; b = -er; c = re-; d = =went; e = i>a; f = k* & !*V: +t | +x; V: a, i";
+        assert_eq!(forms(definition, "rewinder"), cells(&[
+            &[Some("unrewinder")], &[Some("rewind")], &[Some("winder")], &[Some("went")], &[Some("rewander")], &[Some("rewinderx")]
+        ]));
+        assert_eq!(forms(definition, "kap")[5], [Some("kapt".to_string())]);
+        assert_eq!(forms(definition, "ka")[5], [Some("kax".to_string())]);
+        assert_eq!(forms("rows = a; a = o>∅", "foot"), cells(&[&[Some("fot")]]));
+    }
+
+    #[test]
+    fn errors_name_the_entry() {
+        assert_eq!(parse_error("rows = a;; b = +s"), "Entry 2: There is no cell b. Use row.column, like a");
+        assert_eq!(parse_error("rows = a\n\na = s"), "Entry 2: Unknown operation: s. Use +x, x+, -x, x-, x>y, =form or ~");
+        assert_eq!(parse_error("rows = a; a = +s\nnonsense"), "Entry 3 is neither a setting, a category nor a cell: nonsense");
+        assert_eq!(parse_error("rows = a; a = a: +s"), "Entry 2: Condition a needs a * before or after it, like *a for stems ending in a");
+        assert_eq!(parse_error("rows = a; a = *a:"), "Entry 2: *a: has no operations. Use ~ for the unchanged stem");
+        assert_eq!(parse_error("columns = x; a = +s"), "The paradigm has no rows. Example: rows = nominative, accusative");
+        assert_eq!(parse_error("rows = a"), "The paradigm has no cells. Example: nominative.plural = +s");
+    }
+}
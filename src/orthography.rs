@@ -0,0 +1,61 @@
+/// Rewrite rules between phonemic and written forms, such as `ʃ > sh; tʃ > ch`.
+///
+/// Rules are applied in a single pass from left to right. At every position the longest matching rule wins and rules of
+/// the same length are tried in the order they were given, so `tʃ > ch` takes precedence over `ʃ > sh` in either order
+/// and the output of one rule is never rewritten by another.
+pub struct Orthography {
+    rules: Vec<(String, String)>
+}
+
+impl Orthography {
+    pub fn parse(rules: &str) -> Result<Self, String> {
+        let mut parsed = Vec::new();
+        for rule in rules.split([';', '\n']).map(str::trim).filter(|rule| !rule.is_empty()) {
+            let Some((from, to)) = rule.split_once('>') else {
+                return Err(format!("Orthography rule {rule} is missing a >. Example: ʃ>sh"));
+            };
+            let from = from.trim();
+            let to = to.trim().trim_start_matches('∅');
+            if from.is_empty() || from == "∅" {
+                return Err(format!("Orthography rule {rule} does not say what to replace"));
+            }
+            parsed.push((from.to_string(), to.to_string()));
+        }
+
+        if parsed.is_empty() {
+            return Err("Please provide at least one orthography rule. Example: ʃ>sh; tʃ>ch".to_string());
+        }
+        parsed.sort_by_key(|(from, _)| std::cmp::Reverse(from.len()));
+        Ok(Orthography { rules: parsed })
+    }
+
+    pub fn reversed(&self) -> Result<Self, String> {
+        let mut rules = Vec::new();
+        for (from, to) in &self.rules {
+            if to.is_empty() {
+                return Err(format!("Orthography rule {from}>∅ can not be reversed"));
+            }
+            rules.push((to.clone(), from.clone()));
+        }
+        rules.sort_by_key(|(from, _)| std::cmp::Reverse(from.len()));
+        Ok(Orthography { rules })
+    }
+
+    pub fn apply(&self, text: &str) -> String {
+        let mut result = String::with_capacity(text.len());
+        let mut rest = text;
+        while let Some(char) = rest.chars().next() {
+            match self.rules.iter().find(|(from, _)| rest.starts_with(from.as_str())) {
+                Some((from, to)) => {
+                    result.push_str(to);
+                    rest = &rest[from.len()..];
+                }
+                None => {
+                    result.push(char);
+                    rest = &rest[char.len_utf8()..];
+                }
+            }
+        }
+        result
+    }
+}
@@ -0,0 +1,95 @@
+use std::collections::HashMap;
+use rand::Rng;
+use rand::distr::Distribution;
+use rand::distr::weighted::WeightedIndex;
+
+pub struct PhonemeCategory<'a> {
+    pub letters: Vec<&'a str>,
+    weights: WeightedIndex<f64>
+}
+
+pub type Categories<'a> = HashMap<String, PhonemeCategory<'a>>;
+
+impl<'a> PhonemeCategory<'a> {
+    /// A category where every letter is equally likely. Returns `None` if there are no letters.
+    pub fn uniform(letters: Vec<&'a str>) -> Option<Self> {
+        let weights = WeightedIndex::new(vec![1.0; letters.len()]).ok()?;
+        Some(PhonemeCategory { letters, weights })
+    }
+
+    pub fn pick<R: Rng + ?Sized>(&self, rng: &mut R) -> &'a str {
+        self.letters[self.weights.sample(rng)]
+    }
+}
+
+pub fn parse_category_option<'a>(i: &str, option: &'a str, categories: &mut Categories<'a>) -> Result<(), String> {
+    for definition in option.split(";").map(str::trim).filter(|definition| !definition.is_empty()) {
+        let (name, category) = parse_category_definition(i, definition)?;
+        categories.insert(name, category);
+    }
+    Ok(())
+}
+
+pub fn parse_category_definition<'a>(i: &str, definition: &'a str) -> Result<(String, PhonemeCategory<'a>), String> {
+    if let Some((name, letters)) = definition.split_once(":") {
+        let name = name.trim();
+        let name = name.strip_prefix("{").and_then(|name| name.strip_suffix("}")).unwrap_or(name);
+        if !is_valid_category_name(name) {
+            return Err(format!("Category {i} has an invalid name: {name}. Names can not contain spaces or special characters"));
+        }
+
+        Ok((name.to_string(), parse_category(i, letters)?))
+    } else {
+        Err(format!("Category {i} is not formatted correctly. Example: V:a,e,i,o,u"))
+    }
+}
+
+pub fn is_valid_category_name(name: &str) -> bool {
+    !name.is_empty() && !name.contains(|char: char| char.is_whitespace() || "{}()[],;:!#._%|*>/".contains(char))
+}
+
+pub fn parse_category<'a>(i: &str, letters: &'a str) -> Result<PhonemeCategory<'a>, String> {
+    let (zipf, letters) = match letters.strip_prefix("zipf:") {
+        Some(letters) => (true, letters),
+        None => (false, letters)
+    };
+
+    let mut parsed = Vec::new();
+    let mut weights = Vec::new();
+    for (rank, letter) in letters.split(",").enumerate() {
+        if let Some((letter, weight)) = letter.rsplit_once("*") {
+            if zipf {
+                return Err(format!("Category {i} uses zipf mode and can not have explicit weights"));
+            }
+            match weight.parse::<f64>() {
+                Ok(weight) if weight.is_finite() && weight > 0.0 => {
+                    parsed.push(letter);
+                    weights.push(weight);
+                }
+                _ => return Err(format!("Category {i} has an invalid weight for {letter}: {weight}. Weights must be positive numbers"))
+            }
+        } else if zipf {
+            parsed.push(letter);
+            weights.push(1.0 / (rank + 1) as f64);
+        } else {
+            parsed.push(letter);
+            weights.push(1.0);
+        }
+    }
+
+    let weights = WeightedIndex::new(weights)
+        .map_err(|err| format!("Category {i} has invalid weights: {err}"))?;
+    Ok(PhonemeCategory { letters: parsed, weights })
+}
+
+pub fn category_at<'c, T>(categories: &'c HashMap<String, T>, text: &str) -> Option<(&'c str, usize)> {
+    if let Some(rest) = text.strip_prefix("{") {
+        let end = rest.find("}")?;
+        return categories.get_key_value(&rest[..end]).map(|(name, _)| (name.as_str(), end + 2));
+    }
+
+    categories.keys()
+        .filter(|name| text.starts_with(name.as_str()))
+        .max_by_key(|name| name.len())
+        .map(|name| (name.as_str(), name.len()))
+}
@@ -0,0 +1,550 @@
+use serenity::all::ChannelId;
+use unicode_normalization::char::is_combining_mark;
+use unicode_normalization::UnicodeNormalization;
+use unicode_width::UnicodeWidthStr;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Place {
+    Bilabial,
+    Labiodental,
+    Dental,
+    Alveolar,
+    Postalveolar,
+    Retroflex,
+    Palatal,
+    Velar,
+    LabialVelar,
+    Uvular,
+    Pharyngeal,
+    Glottal
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Manner {
+    Plosive,
+    Affricate,
+    Nasal,
+    Trill,
+    Tap,
+    Fricative,
+    LateralFricative,
+    Approximant,
+    LateralApproximant,
+    Implosive,
+    Click
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Height {
+    Close,
+    NearClose,
+    CloseMid,
+    Mid,
+    OpenMid,
+    NearOpen,
+    Open
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Backness {
+    Front,
+    Central,
+    Back
+}
+
+/// What the IPA chart says about a segment, ignoring diacritics other than voicing.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Segment {
+    Consonant { place: Place, manner: Manner, voiced: bool },
+    Vowel { height: Height, backness: Backness, rounded: bool }
+}
+
+const PLACES: [(Place, &str); 12] = [
+    (Place::Bilabial, "Bilabial"), (Place::Labiodental, "Labiodent."), (Place::Dental, "Dental"),
+    (Place::Alveolar, "Alveolar"), (Place::Postalveolar, "Postalv."), (Place::Retroflex, "Retroflex"),
+    (Place::Palatal, "Palatal"), (Place::Velar, "Velar"), (Place::LabialVelar, "Lab.-vel."), (Place::Uvular, "Uvular"),
+    (Place::Pharyngeal, "Pharyng."), (Place::Glottal, "Glottal")
+];
+
+const MANNERS: [(Manner, &str); 11] = [
+    (Manner::Plosive, "Plosive"), (Manner::Affricate, "Affricate"), (Manner::Nasal, "Nasal"), (Manner::Trill, "Trill"),
+    (Manner::Tap, "Tap"), (Manner::Fricative, "Fricative"), (Manner::LateralFricative, "Lat. fricative"),
+    (Manner::Approximant, "Approximant"), (Manner::LateralApproximant, "Lat. approximant"),
+    (Manner::Implosive, "Implosive"), (Manner::Click, "Click")
+];
+
+const HEIGHTS: [(Height, &str); 7] = [
+    (Height::Close, "Close"), (Height::NearClose, "Near-close"), (Height::CloseMid, "Close-mid"), (Height::Mid, "Mid"),
+    (Height::OpenMid, "Open-mid"), (Height::NearOpen, "Near-open"), (Height::Open, "Open")
+];
+
+const CONSONANTS: &[(&str, Place, Manner, bool)] = &[
+    ("p", Place::Bilabial, Manner::Plosive, false), ("b", Place::Bilabial, Manner::Plosive, true),
+    ("t", Place::Alveolar, Manner::Plosive, false), ("d", Place::Alveolar, Manner::Plosive, true),
+    ("ʈ", Place::Retroflex, Manner::Plosive, false), ("ɖ", Place::Retroflex, Manner::Plosive, true),
+    ("c", Place::Palatal, Manner::Plosive, false), ("ɟ", Place::Palatal, Manner::Plosive, true),
+    ("k", Place::Velar, Manner::Plosive, false), ("g", Place::Velar, Manner::Plosive, true),
+    ("ɡ", Place::Velar, Manner::Plosive, true), ("q", Place::Uvular, Manner::Plosive, false),
+    ("ɢ", Place::Uvular, Manner::Plosive, true), ("ʡ", Place::Pharyngeal, Manner::Plosive, false),
+    ("ʔ", Place::Glottal, Manner::Plosive, false),
+    ("m", Place::Bilabial, Manner::Nasal, true), ("ɱ", Place::Labiodental, Manner::Nasal, true),
+    ("n", Place::Alveolar, Manner::Nasal, true), ("ɳ", Place::Retroflex, Manner::Nasal, true),
+    ("ɲ", Place::Palatal, Manner::Nasal, true), ("ŋ", Place::Velar, Manner::Nasal, true),
+    ("ɴ", Place::Uvular, Manner::Nasal, true),
+    ("ʙ", Place::Bilabial, Manner::Trill, true), ("r", Place::Alveolar, Manner::Trill, true),
+    ("ʀ", Place::Uvular, Manner::Trill, true),
+    ("ⱱ", Place::Labiodental, Manner::Tap, true), ("ɾ", Place::Alveolar, Manner::Tap, true),
+    ("ɺ", Place::Alveolar, Manner::Tap, true), ("ɽ", Place::Retroflex, Manner::Tap, true),
+    ("ɸ", Place::Bilabial, Manner::Fricative, false), ("β", Place::Bilabial, Manner::Fricative, true),
+    ("f", Place::Labiodental, Manner::Fricative, false), ("v", Place::Labiodental, Manner::Fricative, true),
+    ("θ", Place::Dental, Manner::Fricative, false), ("ð", Place::Dental, Manner::Fricative, true),
+    ("s", Place::Alveolar, Manner::Fricative, false), ("z", Place::Alveolar, Manner::Fricative, true),
+    ("ʃ", Place::Postalveolar, Manner::Fricative, false), ("ʒ", Place::Postalveolar, Manner::Fricative, true),
+    ("ɕ", Place::Postalveolar, Manner::Fricative, false), ("ʑ", Place::Postalveolar, Manner::Fricative, true),
+    ("ʂ", Place::Retroflex, Manner::Fricative, false), ("ʐ", Place::Retroflex, Manner::Fricative, true),
+    ("ç", Place::Palatal, Manner::Fricative, false), ("ʝ", Place::Palatal, Manner::Fricative, true),
+    ("x", Place::Velar, Manner::Fricative, false), ("ɣ", Place::Velar, Manner::Fricative, true),
+    ("ɧ", Place::Velar, Manner::Fricative, false), ("ʍ", Place::LabialVelar, Manner::Fricative, false),
+    ("χ", Place::Uvular, Manner::Fricative, false), ("ʁ", Place::Uvular, Manner::Fricative, true),
+    ("ħ", Place::Pharyngeal, Manner::Fricative, false), ("ʕ", Place::Pharyngeal, Manner::Fricative, true),
+    ("ʜ", Place::Pharyngeal, Manner::Fricative, false), ("ʢ", Place::Pharyngeal, Manner::Fricative, true),
+    ("h", Place::Glottal, Manner::Fricative, false), ("ɦ", Place::Glottal, Manner::Fricative, true),
+    ("ɬ", Place::Alveolar, Manner::LateralFricative, false), ("ɮ", Place::Alveolar, Manner::LateralFricative, true),
+    ("ʋ", Place::Labiodental, Manner::Approximant, true), ("ɹ", Place::Alveolar, Manner::Approximant, true),
+    ("ɻ", Place::Retroflex, Manner::Approximant, true), ("j", Place::Palatal, Manner::Approximant, true),
+    ("ɥ", Place::Palatal, Manner::Approximant, true), ("ɰ", Place::Velar, Manner::Approximant, true),
+    ("w", Place::LabialVelar, Manner::Approximant, true),
+    ("l", Place::Alveolar, Manner::LateralApproximant, true), ("ɫ", Place::Alveolar, Manner::LateralApproximant, true),
+    ("ɭ", Place::Retroflex, Manner::LateralApproximant, true), ("ʎ", Place::Palatal, Manner::LateralApproximant, true),
+    ("ʟ", Place::Velar, Manner::LateralApproximant, true),
+    ("ɓ", Place::Bilabial, Manner::Implosive, true), ("ɗ", Place::Alveolar, Manner::Implosive, true),
+    ("ʄ", Place::Palatal, Manner::Implosive, true), ("ɠ", Place::Velar, Manner::Implosive, true),
+    ("ʛ", Place::Uvular, Manner::Implosive, true),
+    ("ʘ", Place::Bilabial, Manner::Click, false), ("ǀ", Place::Dental, Manner::Click, false),
+    ("ǃ", Place::Postalveolar, Manner::Click, false), ("ǂ", Place::Palatal, Manner::Click, false),
+    ("ǁ", Place::Alveolar, Manner::Click, false)
+];
+
+const VOWELS: &[(&str, Height, Backness, bool)] = &[
+    ("i", Height::Close, Backness::Front, false), ("y", Height::Close, Backness::Front, true),
+    ("ɨ", Height::Close, Backness::Central, false), ("ʉ", Height::Close, Backness::Central, true),
+    ("ɯ", Height::Close, Backness::Back, false), ("u", Height::Close, Backness::Back, true),
+    ("ɪ", Height::NearClose, Backness::Front, false), ("ʏ", Height::NearClose, Backness::Front, true),
+    ("ᵻ", Height::NearClose, Backness::Central, false), ("ᵿ", Height::NearClose, Backness::Central, true),
+    ("ʊ", Height::NearClose, Backness::Back, true),
+    ("e", Height::CloseMid, Backness::Front, false), ("ø", Height::CloseMid, Backness::Front, true),
+    ("ɘ", Height::CloseMid, Backness::Central, false), ("ɵ", Height::CloseMid, Backness::Central, true),
+    ("ɤ", Height::CloseMid, Backness::Back, false), ("o", Height::CloseMid, Backness::Back, true),
+    ("ə", Height::Mid, Backness::Central, false), ("ɚ", Height::Mid, Backness::Central, false),
+    ("ɛ", Height::OpenMid, Backness::Front, false), ("œ", Height::OpenMid, Backness::Front, true),
+    ("ɜ", Height::OpenMid, Backness::Central, false), ("ɝ", Height::OpenMid, Backness::Central, false),
+    ("ɞ", Height::OpenMid, Backness::Central, true), ("ʌ", Height::OpenMid, Backness::Back, false),
+    ("ɔ", Height::OpenMid, Backness::Back, true),
+    ("æ", Height::NearOpen, Backness::Front, false), ("ɐ", Height::NearOpen, Backness::Central, false),
+    ("a", Height::Open, Backness::Front, false), ("ɶ", Height::Open, Backness::Front, true),
+    ("ɑ", Height::Open, Backness::Back, false), ("ɒ", Height::Open, Backness::Back, true)
+];
+
+/// Classifies an IPA symbol like `pʰ`, `t͡ʃ`, `ã` or `n̥`. Affricates are a stop followed by a fricative and take the
+/// place and voicing of the fricative.
+pub fn classify(symbol: &str) -> Option<Segment> {
+    let mut bases = Vec::new();
+    let mut voicing = None;
+    for char in symbol.nfc() {
+        match char {
+            '\u{325}' | '\u{30A}' => voicing = Some(false),
+            '\u{32C}' => voicing = Some(true),
+            char if is_diacritic(char) => {}
+            char => match classify_char(char) {
+                Some(segment) => bases.push(segment),
+                None => bases.push(char.to_string().nfd().next().and_then(classify_char)?)
+            }
+        }
+    }
+
+    let segment = match bases[..] {
+        [segment] => segment,
+        [Segment::Consonant { manner: Manner::Plosive, .. }, Segment::Consonant { place, manner: Manner::Fricative | Manner::LateralFricative, voiced }] =>
+            Segment::Consonant { place, manner: Manner::Affricate, voiced },
+        _ => return None
+    };
+    Some(match (segment, voicing) {
+        (Segment::Consonant { place, manner, .. }, Some(voiced)) => Segment::Consonant { place, manner, voiced },
+        (segment, _) => segment
+    })
+}
+
+fn classify_char(char: char) -> Option<Segment> {
+    let mut buffer = [0; 4];
+    let char: &str = char.encode_utf8(&mut buffer);
+    if let Some((_, place, manner, voiced)) = CONSONANTS.iter().find(|consonant| consonant.0 == char) {
+        return Some(Segment::Consonant { place: *place, manner: *manner, voiced: *voiced });
+    }
+    VOWELS.iter().find(|vowel| vowel.0 == char)
+        .map(|(_, height, backness, rounded)| Segment::Vowel { height: *height, backness: *backness, rounded: *rounded })
+}
+
+/// Every base symbol of the IPA chart, consonants first.
+pub fn ipa_symbols() -> impl Iterator<Item = &'static str> {
+    CONSONANTS.iter().map(|consonant| consonant.0).chain(VOWELS.iter().map(|vowel| vowel.0))
+}
+
+/// Combining marks and modifier letters such as `ʰ`, `ʷ`, `ʼ` or `ː`.
+fn is_diacritic(char: char) -> bool {
+    is_combining_mark(char) || ('\u{2B0}'..='\u{2FF}').contains(&char) || "ⁿᵝᶣᵊ".contains(char)
+}
+
+pub const MISSING_INVENTORY: &str = "This channel has no phoneme inventory. Set one with /phonology set";
+
+/// The phonemes of a conlang channel by IPA symbol, in the order the owner listed them.
+pub struct Inventory {
+    pub channel: ChannelId,
+    pub consonants: Vec<String>,
+    pub vowels: Vec<String>
+}
+
+impl Inventory {
+    pub fn parse(channel: ChannelId, consonants: &str, vowels: &str) -> Result<Self, String> {
+        let consonants = parse_symbols(consonants, false)?;
+        let vowels = parse_symbols(vowels, true)?;
+        if consonants.is_empty() && vowels.is_empty() {
+            return Err("Please list the consonants or vowels of the language".to_string());
+        }
+        Ok(Inventory { channel, consonants, vowels })
+    }
+
+    fn segments(&self) -> impl Iterator<Item = (&str, Segment)> {
+        self.consonants.iter().chain(&self.vowels)
+            .filter_map(|symbol| classify(symbol).map(|segment| (symbol.as_str(), segment)))
+    }
+
+    /// The phonemes in a class like `stops`, `voiced-fricatives` or `front-rounded-vowels`.
+    pub fn class(&self, name: &str) -> Result<Vec<&str>, String> {
+        let terms = parse_class(name).ok_or(format!("Unknown phoneme class: @{name}. Combine words like voiced, \
+            stops, nasals, labial, velar, front, close or rounded with -, like @voiceless-stops"))?;
+        let members: Vec<&str> = self.segments()
+            .filter(|(_, segment)| terms.iter().all(|term| term.matches(segment)))
+            .map(|(symbol, _)| symbol)
+            .collect();
+        match members.is_empty() {
+            true => Err(format!("The phoneme inventory has no {}", name.replace('-', " "))),
+            false => Ok(members)
+        }
+    }
+
+    /// The consonants as a place × manner table with voiceless phonemes on the left of each cell.
+    pub fn consonant_chart(&self) -> String {
+        let consonants: Vec<(&str, Place, Manner, bool)> = self.segments()
+            .filter_map(|(symbol, segment)| match segment {
+                Segment::Consonant { place, manner, voiced } => Some((symbol, place, manner, voiced)),
+                _ => None
+            })
+            .collect();
+        let places: Vec<(Place, &str)> = PLACES.into_iter()
+            .filter(|(place, _)| consonants.iter().any(|consonant| consonant.1 == *place))
+            .collect();
+        let manners: Vec<(Manner, &str)> = MANNERS.into_iter()
+            .filter(|(manner, _)| consonants.iter().any(|consonant| consonant.2 == *manner))
+            .collect();
+
+        let cell = |place: Place, manner: Manner, voiced: bool| consonants.iter()
+            .filter(|consonant| consonant.1 == place && consonant.2 == manner && consonant.3 == voiced)
+            .map(|consonant| consonant.0)
+            .collect::<Vec<_>>()
+            .join(" ");
+        let voiceless_widths: Vec<usize> = places.iter()
+            .map(|(place, _)| manners.iter().map(|(manner, _)| cell(*place, *manner, false).width()).max().unwrap_or(0))
+            .collect();
+
+        let mut rows = vec![std::iter::once("").chain(places.iter().map(|(_, name)| *name)).map(str::to_string).collect::<Vec<_>>()];
+        for (manner, name) in &manners {
+            rows.push(std::iter::once(name.to_string())
+                .chain(places.iter().zip(&voiceless_widths)
+                    .map(|((place, _), width)| pair(&cell(*place, *manner, false), &cell(*place, *manner, true), *width)))
+                .collect());
+        }
+        align(&rows)
+    }
+
+    /// The vowels in the shape of the IPA vowel trapezoid, with unrounded vowels on the left of each cell.
+    pub fn vowel_chart(&self) -> String {
+        let vowels: Vec<(&str, Height, Backness, bool)> = self.segments()
+            .filter_map(|(symbol, segment)| match segment {
+                Segment::Vowel { height, backness, rounded } => Some((symbol, height, backness, rounded)),
+                _ => None
+            })
+            .collect();
+        if vowels.is_empty() {
+            return String::new();
+        }
+
+        let cell = |height: Height, backness: Backness, rounded: bool| vowels.iter()
+            .filter(|vowel| vowel.1 == height && vowel.2 == backness && vowel.3 == rounded)
+            .map(|vowel| vowel.0)
+            .collect::<Vec<_>>()
+            .join(" ");
+        let column = |backness: Backness, header: &str| {
+            let unrounded = HEIGHTS.iter().map(|(height, _)| cell(*height, backness, false).width()).max().unwrap_or(0);
+            let cells: Vec<String> = HEIGHTS.iter()
+                .map(|(height, _)| pair(&cell(*height, backness, false), &cell(*height, backness, true), unrounded))
+                .collect();
+            let width = cells.iter().map(|cell| cell.width()).chain([header.width()]).max().unwrap_or(0);
+            (cells, width)
+        };
+        let (front, front_width) = column(Backness::Front, "Front");
+        let (central, central_width) = column(Backness::Central, "Central");
+        let (back, _) = column(Backness::Back, "Back");
+
+        let label_width = HEIGHTS.iter().map(|(_, name)| name.width()).max().unwrap_or(0) + 2;
+        let central_x = |idx: usize| label_width + front_width + 8 + idx / 2;
+        let back_x = label_width + front_width + central_width + 13;
+
+        let mut lines = vec![place_at(&[(label_width, "Front"), (central_x(0), "Central"), (back_x, "Back")])];
+        for (idx, (_, name)) in HEIGHTS.iter().enumerate() {
+            if front[idx].is_empty() && central[idx].is_empty() && back[idx].is_empty() {
+                continue;
+            }
+            lines.push(place_at(&[(0, name), (label_width + idx, &front[idx]), (central_x(idx), &central[idx]), (back_x, &back[idx])]));
+        }
+        lines.join("\n")
+    }
+}
+
+/// Splits a list of IPA symbols separated by spaces or commas and checks that they are consonants or vowels.
+fn parse_symbols(symbols: &str, vowels: bool) -> Result<Vec<String>, String> {
+    let mut parsed: Vec<String> = Vec::new();
+    for symbol in symbols.split([' ', ',']).map(str::trim).filter(|symbol| !symbol.is_empty()) {
+        let symbol: String = symbol.nfc().collect();
+        match (classify(&symbol), vowels) {
+            (None, _) => return Err(format!("Unknown IPA symbol: {symbol}. Use the symbols of the IPA chart, with diacritics if needed")),
+            (Some(Segment::Vowel { .. }), false) => return Err(format!("{symbol} is a vowel. List it with the vowels")),
+            (Some(Segment::Consonant { .. }), true) => return Err(format!("{symbol} is a consonant. List it with the consonants")),
+            _ if parsed.contains(&symbol) => {}
+            _ => parsed.push(symbol)
+        }
+    }
+    Ok(parsed)
+}
+
+/// Joins the two halves of a chart cell, padding the left one so the right halves line up within a column.
+fn pair(left: &str, right: &str, left_width: usize) -> String {
+    match (left_width, right.is_empty()) {
+        (_, true) => left.to_string(),
+        (0, false) => right.to_string(),
+        _ => format!("{left}{} {right}", " ".repeat(left_width - left.width()))
+    }
+}
+
+fn align(rows: &[Vec<String>]) -> String {
+    let widths: Vec<usize> = (0..rows[0].len())
+        .map(|column| rows.iter().map(|row| row[column].width()).max().unwrap_or(0))
+        .collect();
+    rows.iter()
+        .map(|row| place_at(&row.iter().enumerate()
+            .map(|(column, cell)| (widths[..column].iter().map(|width| width + 2).sum(), cell.as_str()))
+            .collect::<Vec<_>>()))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// A line with every text starting at the given column.
+fn place_at(texts: &[(usize, &str)]) -> String {
+    let mut line = String::new();
+    for (column, text) in texts {
+        let width = line.width();
+        if width < *column {
+            line.push_str(&" ".repeat(column - width));
+        } else if width > 0 {
+            line.push(' ');
+        }
+        line.push_str(text);
+    }
+    line.trim_end().to_string()
+}
+
+enum Term {
+    Consonant,
+    Vowel,
+    Voiced(bool),
+    Rounded(bool),
+    Places(&'static [Place]),
+    Manners(&'static [Manner]),
+    Height(&'static [Height]),
+    Backness(Backness)
+}
+
+impl Term {
+    fn parse(word: &str) -> Option<Term> {
+        Some(match word {
+            "consonant" => Term::Consonant,
+            "vowel" => Term::Vowel,
+            "voiced" => Term::Voiced(true),
+            "voiceless" | "unvoiced" => Term::Voiced(false),
+            "rounded" => Term::Rounded(true),
+            "unrounded" => Term::Rounded(false),
+            "labial" => Term::Places(&[Place::Bilabial, Place::Labiodental, Place::LabialVelar]),
+            "bilabial" => Term::Places(&[Place::Bilabial]),
+            "labiodental" => Term::Places(&[Place::Labiodental]),
+            "coronal" => Term::Places(&[Place::Dental, Place::Alveolar, Place::Postalveolar, Place::Retroflex]),
+            "dental" => Term::Places(&[Place::Dental]),
+            "alveolar" => Term::Places(&[Place::Alveolar]),
+            "postalveolar" => Term::Places(&[Place::Postalveolar]),
+            "retroflex" => Term::Places(&[Place::Retroflex]),
+            "palatal" => Term::Places(&[Place::Palatal]),
+            "dorsal" => Term::Places(&[Place::Palatal, Place::Velar, Place::LabialVelar, Place::Uvular]),
+            "velar" => Term::Places(&[Place::Velar, Place::LabialVelar]),
+            "uvular" => Term::Places(&[Place::Uvular]),
+            "pharyngeal" => Term::Places(&[Place::Pharyngeal]),
+            "glottal" => Term::Places(&[Place::Glottal]),
+            "stop" | "plosive" => Term::Manners(&[Manner::Plosive]),
+            "affricate" => Term::Manners(&[Manner::Affricate]),
+            "nasal" => Term::Manners(&[Manner::Nasal]),
+            "trill" => Term::Manners(&[Manner::Trill]),
+            "tap" | "flap" => Term::Manners(&[Manner::Tap]),
+            "fricative" => Term::Manners(&[Manner::Fricative, Manner::LateralFricative]),
+            "approximant" => Term::Manners(&[Manner::Approximant, Manner::LateralApproximant]),
+            "lateral" => Term::Manners(&[Manner::LateralFricative, Manner::LateralApproximant]),
+            "liquid" => Term::Manners(&[Manner::Trill, Manner::Tap, Manner::LateralApproximant]),
+            "implosive" => Term::Manners(&[Manner::Implosive]),
+            "click" => Term::Manners(&[Manner::Click]),
+            "obstruent" => Term::Manners(&[Manner::Plosive, Manner::Affricate, Manner::Fricative, Manner::LateralFricative]),
+            "sonorant" => Term::Manners(&[Manner::Nasal, Manner::Trill, Manner::Tap, Manner::Approximant, Manner::LateralApproximant]),
+            "close" | "high" => Term::Height(&[Height::Close, Height::NearClose]),
+            "near-close" => Term::Height(&[Height::NearClose]),
+            "close-mid" => Term::Height(&[Height::CloseMid]),
+            "mid" => Term::Height(&[Height::CloseMid, Height::Mid, Height::OpenMid]),
+            "open-mid" => Term::Height(&[Height::OpenMid]),
+            "near-open" => Term::Height(&[Height::NearOpen]),
+            "open" | "low" => Term::Height(&[Height::NearOpen, Height::Open]),
+            "front" => Term::Backness(Backness::Front),
+            "central" => Term::Backness(Backness::Central),
+            "back" => Term::Backness(Backness::Back),
+            _ => return word.strip_suffix('s').and_then(Term::parse)
+        })
+    }
+
+    fn matches(&self, segment: &Segment) -> bool {
+        match (self, segment) {
+            (Term::Consonant, Segment::Consonant { .. }) | (Term::Vowel, Segment::Vowel { .. }) => true,
+            (Term::Voiced(expected), Segment::Consonant { voiced, .. }) => voiced == expected,
+            (Term::Voiced(expected), Segment::Vowel { .. }) => *expected,
+            (Term::Rounded(expected), Segment::Vowel { rounded, .. }) => rounded == expected,
+            (Term::Places(places), Segment::Consonant { place, .. }) => places.contains(place),
+            (Term::Manners(manners), Segment::Consonant { manner, .. }) => manners.contains(manner),
+            (Term::Height(heights), Segment::Vowel { height, .. }) => heights.contains(height),
+            (Term::Backness(expected), Segment::Vowel { backness, .. }) => backness == expected,
+            _ => false
+        }
+    }
+}
+
+/// Reads the words of a class name, where two-word terms like `close-mid` take precedence.
+fn parse_class(name: &str) -> Option<Vec<Term>> {
+    let words: Vec<&str> = name.split('-').collect();
+    let mut terms = Vec::new();
+    let mut idx = 0;
+    while idx < words.len() {
+        if let Some(term) = words.get(idx + 1).and_then(|next| Term::parse(&format!("{}-{next}", words[idx]))) {
+            terms.push(term);
+            idx += 2;
+        } else {
+            terms.push(Term::parse(words[idx])?);
+            idx += 1;
+        }
+    }
+    Some(terms)
+}
+
+/// The class name in a category letter like `@stops`, if it is one.
+pub fn class_reference(letter: &str) -> Option<&str> {
+    letter.trim().strip_prefix('@').filter(|name| parse_class(name).is_some())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn consonant(place: Place, manner: Manner, voiced: bool) -> Option<Segment> {
+        Some(Segment::Consonant { place, manner, voiced })
+    }
+
+    fn vowel(height: Height, backness: Backness, rounded: bool) -> Option<Segment> {
+        Some(Segment::Vowel { height, backness, rounded })
+    }
+
+    fn inventory() -> Inventory {
+        Inventory::parse(ChannelId::new(1), "p t k b d g m n ŋ s z ʃ t͡ʃ l r w j", "i y u e o ɛ a").unwrap()
+    }
+
+    #[test]
+    fn classify_consonants() {
+        assert_eq!(classify("p"), consonant(Place::Bilabial, Manner::Plosive, false));
+        assert_eq!(classify("ɡ"), consonant(Place::Velar, Manner::Plosive, true));
+        assert_eq!(classify("ŋ"), consonant(Place::Velar, Manner::Nasal, true));
+        assert_eq!(classify("ʃ"), consonant(Place::Postalveolar, Manner::Fricative, false));
+        assert_eq!(classify("ɾ"), consonant(Place::Alveolar, Manner::Tap, true));
+        assert_eq!(classify("l"), consonant(Place::Alveolar, Manner::LateralApproximant, true));
+        assert_eq!(classify("ʔ"), consonant(Place::Glottal, Manner::Plosive, false));
+    }
+
+    #[test]
+    fn classify_diacritics_and_affricates() {
+        assert_eq!(classify("pʰ"), classify("p"));
+        assert_eq!(classify("kʷʼ"), classify("k"));
+        assert_eq!(classify("n̥"), consonant(Place::Alveolar, Manner::Nasal, false));
+        assert_eq!(classify("s̬"), consonant(Place::Alveolar, Manner::Fricative, true));
+        assert_eq!(classify("t͡ʃ"), consonant(Place::Postalveolar, Manner::Affricate, false));
+        assert_eq!(classify("dʒ"), consonant(Place::Postalveolar, Manner::Affricate, true));
+        assert_eq!(classify("t͡ɬ"), consonant(Place::Alveolar, Manner::Affricate, false));
+    }
+
+    #[test]
+    fn classify_vowels() {
+        assert_eq!(classify("i"), vowel(Height::Close, Backness::Front, false));
+        assert_eq!(classify("y"), vowel(Height::Close, Backness::Front, true));
+        assert_eq!(classify("u"), vowel(Height::Close, Backness::Back, true));
+        assert_eq!(classify("ɛ"), vowel(Height::OpenMid, Backness::Front, false));
+        assert_eq!(classify("ø"), vowel(Height::CloseMid, Backness::Front, true));
+        assert_eq!(classify("ã"), classify("a"));
+        assert_eq!(classify("aː"), classify("a"));
+        assert_eq!(classify("ə"), vowel(Height::Mid, Backness::Central, false));
+    }
+
+    #[test]
+    fn unknown_symbols() {
+        for symbol in ["", "Q", "3", "?", "ai", "pk", "sp", "ʰ"] {
+            assert_eq!(classify(symbol), None, "{symbol}");
+        }
+        assert_eq!(Inventory::parse(ChannelId::new(1), "p Q", "").err().unwrap(),
+            "Unknown IPA symbol: Q. Use the symbols of the IPA chart, with diacritics if needed");
+        assert_eq!(Inventory::parse(ChannelId::new(1), "p a", "").err().unwrap(), "a is a vowel. List it with the vowels");
+        assert_eq!(Inventory::parse(ChannelId::new(1), "", "a p").err().unwrap(), "p is a consonant. List it with the consonants");
+        assert_eq!(Inventory::parse(ChannelId::new(1), " ", ",").err().unwrap(), "Please list the consonants or vowels of the language");
+    }
+
+    #[test]
+    fn class_terms() {
+        let inventory = inventory();
+        assert_eq!(inventory.class("stops").unwrap(), ["p", "t", "k", "b", "d", "g"]);
+        assert_eq!(inventory.class("voiceless-stops").unwrap(), ["p", "t", "k"]);
+        assert_eq!(inventory.class("voiced-fricatives").unwrap(), ["z"]);
+        assert_eq!(inventory.class("velar-nasal").unwrap(), ["ŋ"]);
+        assert_eq!(inventory.class("coronal-obstruents").unwrap(), ["t", "d", "s", "z", "ʃ", "t͡ʃ"]);
+        assert_eq!(inventory.class("liquids").unwrap(), ["l", "r"]);
+        assert_eq!(inventory.class("labial").unwrap(), ["p", "b", "m", "w"]);
+        assert_eq!(inventory.class("front-rounded-vowels").unwrap(), ["y"]);
+        assert_eq!(inventory.class("close-mid").unwrap(), ["e", "o"]);
+        assert_eq!(inventory.class("mid-vowels").unwrap(), ["e", "o", "ɛ"]);
+        assert_eq!(inventory.class("high-back").unwrap(), ["u"]);
+    }
+
+    #[test]
+    fn class_errors() {
+        let inventory = inventory();
+        assert_eq!(inventory.class("uvular-stops").err().unwrap(), "The phoneme inventory has no uvular stops");
+        assert!(inventory.class("sibilants").err().unwrap().starts_with("Unknown phoneme class: @sibilants."));
+        assert_eq!(class_reference(" @voiced-stops"), Some("voiced-stops"));
+        assert_eq!(class_reference("@sibilants"), None);
+        assert_eq!(class_reference("stops"), None);
+    }
+}
@@ -0,0 +1,30 @@
+use std::sync::Arc;
+use serenity::all::{ChannelId, UserId};
+use sqlx::Error;
+use crate::database::{get_preset, get_shared_preset, SqlPool, SqlResult};
+use crate::wordgen::Definition;
+
+/// Saved /wordgen settings, stored in the format of definition files.
+pub struct WordgenPreset {
+    pub owner: UserId,
+    pub name: String,
+    pub channel: Option<u64>,
+    pub definition: String
+}
+
+impl WordgenPreset {
+    pub fn channel(&self) -> Option<ChannelId> {
+        self.channel.map(ChannelId::new)
+    }
+
+    pub fn definition(&self) -> Result<Definition, String> {
+        Definition::parse_file(&self.definition).map_err(|err| format!("Preset {} is broken: {err}", self.name))
+    }
+}
+
+pub async fn find_preset(pool: Arc<SqlPool>, user: UserId, channel: ChannelId, name: &str) -> SqlResult<WordgenPreset> {
+    match get_preset(pool.clone(), user, name).await {
+        Err(Error::RowNotFound) => get_shared_preset(pool, channel, name).await,
+        result => result
+    }
+}
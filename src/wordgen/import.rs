@@ -0,0 +1,594 @@
+use std::collections::HashMap;
+use crate::phonemes::{category_at, is_valid_category_name};
+use super::Definition;
+
+/// A wordgen definition translated from another generator, together with everything that could not be translated.
+pub struct Import {
+    pub definition: Definition,
+    pub warnings: Vec<String>
+}
+
+pub fn import(format: &str, content: &str) -> Import {
+    match format {
+        "awkwords" => import_awkwords(content),
+        "gen" => import_gen(content),
+        _ => import_lexifer(content)
+    }
+}
+
+/// Awkwords: `C:p/t/k*2` subpatterns, `r:CV(C)` as the word pattern, `[a/b]` alternatives, `"x"` literals and `^x` filters.
+pub fn import_awkwords(content: &str) -> Import {
+    let mut entries = Vec::new();
+    let mut warnings = Vec::new();
+    for line in lines(content) {
+        match line.split_once(':') {
+            Some((key, value)) => entries.push((key.trim(), value.trim())),
+            None => warnings.push(format!("Unrecognized line: {line}"))
+        }
+    }
+
+    let is_subpattern = |key: &str| key.len() == 1 && key.chars().all(|char| char.is_ascii_uppercase());
+    let mut importer = Importer::new(entries.iter()
+        .filter(|(key, _)| is_subpattern(key))
+        .map(|(key, _)| key.to_string()));
+    importer.warnings = warnings;
+
+    for (key, value) in entries {
+        match key {
+            "r" => importer.definition.syllable = importer.pattern(value),
+            "n" => {}
+            key if is_subpattern(key) => {
+                let letters = importer.subpattern(key, value);
+                importer.category(key, letters);
+            }
+            key => importer.warn(format!("Setting {key} is not supported"))
+        }
+    }
+
+    importer.definition.min_syllables = Some(1);
+    importer.definition.max_syllables = Some(1);
+    importer.finish()
+}
+
+/// zompist's Gen: `V=aeiou` categories, `ki|chi` rewrite rules and one syllable type per line.
+pub fn import_gen(content: &str) -> Import {
+    let mut importer = Importer::new(lines(content)
+        .filter_map(|line| line.split_once('='))
+        .map(|(name, _)| name.trim().to_string()));
+
+    let mut syllables = Vec::new();
+    for line in lines(content) {
+        if let Some((name, letters)) = line.split_once('=') {
+            let letters = letters.trim();
+            let letters = if letters.contains(char::is_whitespace) {
+                letters.split_whitespace().map(|letter| (letter.to_string(), 1.0)).collect()
+            } else {
+                letters.chars().map(|letter| (letter.to_string(), 1.0)).collect()
+            };
+            importer.category(name.trim(), letters);
+        } else if let Some((pattern, replacement)) = line.split_once('|') {
+            importer.filter(line, pattern.trim(), Some(replacement.trim()));
+        } else {
+            for syllable in line.split_whitespace() {
+                syllables.extend(importer.pattern(syllable));
+            }
+        }
+    }
+
+    if !syllables.is_empty() {
+        importer.definition.syllable = Some(syllables.join(","));
+    }
+    importer.definition.min_syllables = Some(1);
+    importer.definition.max_syllables = Some(3);
+    importer.warn("Gen does not store how many syllables a word has, so words get 1 to 3 syllables".to_string());
+    importer.finish()
+}
+
+/// Lexifer and Vulgarlang-style definitions: `C = p t:2 k` categories, `$S = CV` macros, `words:` or `syllables:`
+/// patterns, `reject:` sequences and `filter: a > b; c > !` rewrites.
+pub fn import_lexifer(content: &str) -> Import {
+    let mut categories = Vec::new();
+    let mut macros = Vec::new();
+    let mut settings = Vec::new();
+    let mut warnings = Vec::new();
+    for line in lines(content) {
+        let equals = line.find('=').unwrap_or(line.len());
+        let colon = line.find(':').unwrap_or(line.len());
+        if equals < colon {
+            let (name, value) = (line[..equals].trim(), line[equals + 1..].trim());
+            if name.starts_with('$') {
+                macros.push((name, value));
+            } else {
+                categories.push((name, value));
+            }
+        } else if colon < equals {
+            settings.push((line[..colon].trim().to_lowercase(), line[colon + 1..].trim()));
+        } else {
+            warnings.push(format!("Unrecognized line: {line}"));
+        }
+    }
+    macros.sort_by_key(|(name, _)| std::cmp::Reverse(name.len()));
+
+    let mut importer = Importer::new(categories.iter().map(|(name, _)| name.to_string()));
+    importer.warnings = warnings;
+
+    for (name, value) in categories {
+        let mut letters = Vec::new();
+        for letter in value.split_whitespace() {
+            let Some((letter, weight)) = letter.rsplit_once([':', '*']) else {
+                letters.push((letter.to_string(), 1.0));
+                continue;
+            };
+            match weight.parse::<f64>() {
+                Ok(weight) if weight.is_finite() && weight > 0.0 => letters.push((letter.to_string(), weight)),
+                _ => {
+                    importer.warn(format!("Category {name}: {letter} has an invalid weight: {weight}"));
+                    letters.push((letter.to_string(), 1.0));
+                }
+            }
+        }
+        importer.category(name, letters);
+    }
+
+    let mut words = Vec::new();
+    let mut syllables = Vec::new();
+    for (key, value) in settings {
+        match key.as_str() {
+            "words" | "syllables" | "syllable" | "syllable structure" => for pattern in value.split_whitespace() {
+                let Some(pattern) = importer.expand_macros(pattern, &macros) else {
+                    continue;
+                };
+                let patterns = if key == "words" { &mut words } else { &mut syllables };
+                patterns.extend(importer.pattern(&pattern));
+            },
+            "reject" => for sequence in value.split_whitespace() {
+                for alternative in sequence.split('|') {
+                    importer.filter(sequence, alternative, None);
+                }
+            },
+            "filter" => for rule in value.split(';').map(str::trim).filter(|rule| !rule.is_empty()) {
+                match rule.split_once('>').map(|(pattern, replacement)| (pattern.trim(), replacement.trim())) {
+                    Some((pattern, "!")) => importer.filter(rule, pattern, None),
+                    Some((pattern, replacement)) => importer.filter(rule, pattern, Some(replacement)),
+                    None => importer.warn(format!("Filter {rule} is missing a >"))
+                }
+            },
+            key => importer.warn(format!("Setting {key} is not supported"))
+        }
+    }
+
+    if !words.is_empty() {
+        if !syllables.is_empty() {
+            importer.warn("Both words and syllables are given, so only words is used".to_string());
+        }
+        importer.definition.syllable = Some(words.join(","));
+        importer.definition.min_syllables = Some(1);
+        importer.definition.max_syllables = Some(1);
+    } else {
+        if !syllables.is_empty() {
+            importer.definition.syllable = Some(syllables.join(","));
+        }
+        importer.definition.min_syllables = Some(1);
+        importer.definition.max_syllables = Some(3);
+        importer.warn("The definition does not say how many syllables a word has, so words get 1 to 3 syllables".to_string());
+    }
+    importer.finish()
+}
+
+fn lines(content: &str) -> impl Iterator<Item = &str> {
+    content.lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+}
+
+struct Importer {
+    definition: Definition,
+    names: HashMap<String, ()>,
+    filters: Vec<String>,
+    warnings: Vec<String>
+}
+
+impl Importer {
+    fn new(names: impl Iterator<Item = String>) -> Self {
+        Importer {
+            definition: Definition::default(),
+            names: names.map(|name| (name, ())).collect(),
+            filters: Vec::new(),
+            warnings: Vec::new()
+        }
+    }
+
+    fn warn(&mut self, warning: String) {
+        self.warnings.push(warning);
+    }
+
+    fn category(&mut self, name: &str, letters: Vec<(String, f64)>) {
+        if !is_valid_category_name(name) {
+            self.warn(format!("Category {name} was skipped because its name contains spaces or special characters"));
+            return;
+        }
+
+        let mut rendered = Vec::new();
+        for (letter, weight) in letters {
+            if letter.contains(|char: char| char.is_whitespace() || ",;*".contains(char)) {
+                self.warn(format!("Category {name}: {letter} was skipped because letters can not contain commas, semicolons or *"));
+            } else if weight == 1.0 {
+                rendered.push(letter);
+            } else {
+                rendered.push(format!("{letter}*{weight}"));
+            }
+        }
+
+        if rendered.is_empty() {
+            self.warn(format!("Category {name} was skipped because it has no usable letters"));
+        } else {
+            self.definition.categories.push((name.to_string(), format!("{name}:{}", rendered.join(","))));
+        }
+    }
+
+    fn read(&mut self, source: &str) -> Result<Vec<Alternative>, String> {
+        let mut reader = PatternReader { text: source, pos: 0, names: &self.names, filters: Vec::new() };
+        let alternatives = reader.read_alternatives(None);
+        for filter in std::mem::take(&mut reader.filters) {
+            self.filter(&format!("^{filter}"), &filter, None);
+        }
+        alternatives
+    }
+
+    fn pattern(&mut self, source: &str) -> Option<String> {
+        let rendered = self.read(source).and_then(|alternatives| {
+            if alternatives.iter().any(|alternative| alternative.pieces.is_empty()) {
+                return Err("Patterns can not have empty alternatives".to_string());
+            }
+            render_alternatives(alternatives.iter(), ",", false)
+        });
+
+        match rendered {
+            Ok(rendered) => Some(rendered),
+            Err(err) => {
+                self.warn(format!("Pattern {source} was skipped: {err}"));
+                None
+            }
+        }
+    }
+
+    fn subpattern(&mut self, name: &str, source: &str) -> Vec<(String, f64)> {
+        let alternatives = match self.read(source) {
+            Ok(alternatives) => alternatives,
+            Err(err) => {
+                self.warn(format!("Subpattern {name} was skipped: {err}"));
+                return Vec::new();
+            }
+        };
+
+        let mut letters = Vec::new();
+        for alternative in alternatives {
+            let letter: Option<String> = alternative.pieces.iter().map(|piece| match piece {
+                Piece::Literal(literal) => Some(literal.as_str()),
+                _ => None
+            }).collect();
+            match letter {
+                Some(letter) => letters.push((letter, alternative.weight)),
+                None => self.warn(format!("Subpattern {name}: nested patterns can not be used in categories, so one option was skipped"))
+            }
+        }
+        letters
+    }
+
+    fn filter(&mut self, rule: &str, pattern: &str, replacement: Option<&str>) {
+        let mut filter = pattern.to_string();
+        if let Some(rest) = filter.strip_prefix('^') {
+            filter = format!("#{rest}");
+        }
+        if let Some(rest) = filter.strip_suffix('$') {
+            filter = format!("{rest}#");
+        }
+
+        let special = |text: &str| text.contains(|char: char| char.is_whitespace() || "[](){}.*+?\\|^$>".contains(char));
+        if filter.trim_matches('#').is_empty() || special(&filter) || replacement.is_some_and(|replacement| special(replacement) || replacement.contains('#')) {
+            self.warn(format!("Rule {rule} was skipped because it can not be expressed as a filter"));
+            return;
+        }
+        let has_category = filter.char_indices().any(|(idx, _)| category_at(&self.names, &filter[idx..]).is_some());
+        if has_category && replacement.is_some_and(|replacement| !replacement.is_empty()) {
+            self.warn(format!("Rule {rule} was skipped because filters can only replace categories with fixed text"));
+            return;
+        }
+
+        match replacement {
+            Some(replacement) => self.filters.push(format!("{filter}>{replacement}")),
+            None => self.filters.push(filter)
+        }
+    }
+
+    fn expand_macros(&mut self, pattern: &str, macros: &[(&str, &str)]) -> Option<String> {
+        let mut expanded = pattern.to_string();
+        for _ in 0..10 {
+            if !expanded.contains('$') {
+                return Some(expanded);
+            }
+            for (name, value) in macros {
+                expanded = expanded.replace(name, value);
+            }
+        }
+        self.warn(format!("Pattern {pattern} was skipped because it uses an undefined or recursive macro"));
+        None
+    }
+
+    fn finish(mut self) -> Import {
+        if !self.filters.is_empty() {
+            self.definition.filter = Some(self.filters.join(" "));
+        }
+        if self.definition.syllable.is_none() {
+            self.warn("No usable pattern was found".to_string());
+        }
+        Import { definition: self.definition, warnings: self.warnings }
+    }
+}
+
+enum Piece {
+    Category(String),
+    Literal(String),
+    Group(Vec<Alternative>, bool)
+}
+
+struct Alternative {
+    pieces: Vec<Piece>,
+    weight: f64
+}
+
+struct PatternReader<'a> {
+    text: &'a str,
+    pos: usize,
+    names: &'a HashMap<String, ()>,
+    filters: Vec<String>
+}
+
+impl PatternReader<'_> {
+    fn peek(&self) -> Option<char> {
+        self.text[self.pos..].chars().next()
+    }
+
+    fn read_alternatives(&mut self, close: Option<char>) -> Result<Vec<Alternative>, String> {
+        let mut alternatives = Vec::new();
+        loop {
+            let pieces = self.read_pieces()?;
+            let weight = self.read_weight()?;
+            alternatives.push(Alternative { pieces, weight });
+
+            match (self.peek(), close) {
+                (Some('/'), _) => self.pos += 1,
+                (None, None) => return Ok(alternatives),
+                (None, Some(']')) => return Err("A [ is never closed".to_string()),
+                (None, Some(_)) => return Err("A ( is never closed".to_string()),
+                (Some(char), Some(close)) if char == close => {
+                    self.pos += 1;
+                    return Ok(alternatives);
+                }
+                (Some(char), _) => return Err(format!("Unexpected {char}"))
+            }
+        }
+    }
+
+    fn read_weight(&mut self) -> Result<f64, String> {
+        if self.peek() != Some('*') {
+            return Ok(1.0);
+        }
+
+        self.pos += 1;
+        let length = self.text[self.pos..].find(|char: char| !char.is_ascii_digit() && char != '.')
+            .unwrap_or(self.text.len() - self.pos);
+        let weight = &self.text[self.pos..self.pos + length];
+        self.pos += length;
+        match weight.parse::<f64>() {
+            Ok(weight) if weight.is_finite() && weight > 0.0 => Ok(weight),
+            _ => Err(format!("Invalid weight: *{weight}"))
+        }
+    }
+
+    fn read_pieces(&mut self) -> Result<Vec<Piece>, String> {
+        let mut pieces = Vec::new();
+        while let Some(char) = self.peek() {
+            match char {
+                '/' | '*' | ']' | ')' => break,
+                char if char.is_whitespace() => self.pos += char.len_utf8(),
+                '[' | '(' => {
+                    self.pos += 1;
+                    let alternatives = self.read_alternatives(Some(if char == '[' { ']' } else { ')' }))?;
+                    pieces.push(Piece::Group(alternatives, char == '('));
+                }
+                '?' => {
+                    self.pos += 1;
+                    let Some(last) = pieces.pop() else {
+                        return Err("? must follow a letter or group".to_string());
+                    };
+                    pieces.push(Piece::Group(vec![Alternative { pieces: vec![last], weight: 1.0 }], true));
+                }
+                '"' => {
+                    self.pos += 1;
+                    let Some(length) = self.text[self.pos..].find('"') else {
+                        return Err("A \" is never closed".to_string());
+                    };
+                    pieces.push(Piece::Literal(self.text[self.pos..self.pos + length].to_string()));
+                    self.pos += length + 1;
+                }
+                '^' => {
+                    self.pos += 1;
+                    let length = self.text[self.pos..].find(['/', ']', ')', '^']).unwrap_or(self.text.len() - self.pos);
+                    let filter = self.text[self.pos..self.pos + length].trim();
+                    if !filter.is_empty() {
+                        self.filters.push(filter.to_string());
+                    }
+                    self.pos += length;
+                }
+                char => match category_at(self.names, &self.text[self.pos..]) {
+                    Some((name, length)) => {
+                        pieces.push(Piece::Category(name.to_string()));
+                        self.pos += length;
+                    }
+                    None => {
+                        pieces.push(Piece::Literal(char.to_string()));
+                        self.pos += char.len_utf8();
+                    }
+                }
+            }
+        }
+        Ok(pieces)
+    }
+}
+
+fn render_alternatives<'a>(alternatives: impl Iterator<Item = &'a Alternative>, separator: &str, literals: bool) -> Result<String, String> {
+    let mut rendered = Vec::new();
+    for alternative in alternatives {
+        let mut text = render_pieces(&alternative.pieces, literals)?;
+        if alternative.weight != 1.0 {
+            text.push_str(&format!("*{}", alternative.weight));
+        }
+        rendered.push(text);
+    }
+    Ok(rendered.join(separator))
+}
+
+fn render_pieces(pieces: &[Piece], literals: bool) -> Result<String, String> {
+    let mut result = String::new();
+    let mut pending = String::new();
+    let flush = |result: &mut String, pending: &mut String| {
+        if !pending.is_empty() {
+            if literals {
+                result.push_str(pending);
+            } else {
+                result.push_str(&format!("[{pending}]"));
+            }
+            pending.clear();
+        }
+    };
+
+    for piece in pieces {
+        match piece {
+            Piece::Literal(literal) => {
+                if let Some(char) = literal.chars().find(|char| char.is_whitespace() || ",*|()[]!{%".contains(*char)) {
+                    return Err(format!("{char} can not be used as a letter"));
+                }
+                pending.push_str(literal);
+            }
+            Piece::Category(name) => {
+                flush(&mut result, &mut pending);
+                if name.chars().count() > 1 {
+                    result.push_str(&format!("{{{name}}}"));
+                } else {
+                    result.push_str(name);
+                }
+            }
+            Piece::Group(alternatives, optional) => {
+                flush(&mut result, &mut pending);
+                result.push_str(&render_group(alternatives, *optional, literals)?);
+            }
+        }
+    }
+    flush(&mut result, &mut pending);
+    Ok(result)
+}
+
+fn render_group(alternatives: &[Alternative], optional: bool, literals: bool) -> Result<String, String> {
+    let kept: Vec<&Alternative> = alternatives.iter().filter(|alternative| !alternative.pieces.is_empty()).collect();
+    if kept.is_empty() {
+        return Ok(String::new());
+    }
+
+    let total: f64 = alternatives.iter().map(|alternative| alternative.weight).sum();
+    let mut probability = kept.iter().map(|alternative| alternative.weight).sum::<f64>() / total * 100.0;
+    if optional {
+        probability /= 2.0;
+    }
+
+    let inner = if kept.len() == 1 {
+        render_pieces(&kept[0].pieces, literals)?
+    } else {
+        format!("[{}]", render_alternatives(kept.into_iter(), "|", true)?)
+    };
+
+    if probability >= 100.0 {
+        Ok(inner)
+    } else if probability == 50.0 {
+        Ok(format!("({inner})"))
+    } else {
+        Ok(format!("({inner})%{}", (probability * 10.0).round() / 10.0))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::wordgen::{expand_nodes, Generator};
+
+    /// Every syllable the compiled generator can build for a one-syllable word.
+    fn syllables(generator: &Generator) -> Vec<String> {
+        let mut syllables: Vec<String> = generator.patterns.for_position(0, 1).syllables.iter()
+            .flat_map(|syllable| expand_nodes(&syllable.nodes, &generator.categories, vec![String::new()]))
+            .collect();
+        syllables.sort();
+        syllables.dedup();
+        syllables
+    }
+
+    fn letters<'g>(generator: &'g Generator, name: &str) -> &'g [&'g str] {
+        &generator.categories[name].letters
+    }
+
+    #[test]
+    fn awkwords() {
+        let import = import("awkwords", "C:p/t/k*2\nV:a/i\nr:CV[\"ng\"/C]^ii\nn:10\nx:1");
+        let definition = &import.definition;
+        assert_eq!(definition.syllable.as_deref(), Some("CV[ng|C]"));
+        assert_eq!(definition.categories, [("C".to_string(), "C:p,t,k*2".to_string()), ("V".to_string(), "V:a,i".to_string())]);
+        assert_eq!(definition.filter.as_deref(), Some("ii"));
+        assert_eq!(import.warnings, ["Setting x is not supported"]);
+
+        let generator = definition.compile().unwrap();
+        assert_eq!(letters(&generator, "C"), ["p", "t", "k"]);
+        assert_eq!(syllables(&generator).len(), 3 * 2 * 4);
+        assert!(syllables(&generator).contains(&"pang".to_string()));
+        assert!(syllables(&generator).contains(&"kik".to_string()));
+    }
+
+    #[test]
+    fn gen() {
+        let import = import("gen", "# A comment\nC=ptk\nV=ai\nN=m n\nCV Chi CVN\nki|chi");
+        let definition = &import.definition;
+        assert_eq!(definition.syllable.as_deref(), Some("CV,C[hi],CVN"));
+        assert_eq!(definition.filter.as_deref(), Some("ki>chi"));
+
+        let generator = definition.compile().unwrap();
+        assert_eq!(letters(&generator, "N"), ["m", "n"]);
+        assert_eq!(syllables(&generator), [
+            "ka", "kam", "kan", "khi", "ki", "kim", "kin",
+            "pa", "pam", "pan", "phi", "pi", "pim", "pin",
+            "ta", "tam", "tan", "thi", "ti", "tim", "tin"
+        ]);
+    }
+
+    #[test]
+    fn lexifer() {
+        let import = import("lexifer", "C = p t:2 k\nV = a i\n$S = CV\nwords: $S$S? $S\nreject: ii|aa\nfilter: k > c; p > !");
+        let definition = &import.definition;
+        assert_eq!(definition.syllable.as_deref(), Some("CVC(V),CV"));
+        assert_eq!(definition.categories[0].1, "C:p,t*2,k");
+        assert_eq!(definition.filter.as_deref(), Some("ii aa k>c p"));
+        assert_eq!((definition.min_syllables, definition.max_syllables), (Some(1), Some(1)));
+
+        let generator = definition.compile().unwrap();
+        assert_eq!(letters(&generator, "V"), ["a", "i"]);
+        assert_eq!(syllables(&generator).len(), 3 * 2 * 3 * 2 + 3 * 2 * 3 + 3 * 2);
+        assert!(syllables(&generator).contains(&"tata".to_string()));
+    }
+
+    #[test]
+    fn unsupported_patterns_are_warnings() {
+        let import = import("lexifer", "C = p t\nwords: C$X C\nfilter: C > a");
+        assert_eq!(import.definition.syllable.as_deref(), Some("C"));
+        assert_eq!(import.warnings, [
+            "Pattern C$X was skipped because it uses an undefined or recursive macro",
+            "Rule C > a was skipped because filters can only replace categories with fixed text"
+        ]);
+    }
+}
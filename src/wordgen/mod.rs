@@ -0,0 +1,1011 @@
+use std::collections::{HashMap, HashSet};
+use rand::{Rng, RngExt};
+use rand::distr::Distribution;
+use rand::distr::weighted::WeightedIndex;
+use crate::orthography::Orthography;
+use prosody::Prosody;
+use crate::phonemes::{category_at, parse_category_definition, Categories};
+use crate::features::{find_classes, NaturalClass};
+use crate::phonology::{class_reference, Inventory, MISSING_INVENTORY};
+use crate::xsampa::{to_ipa, Notation};
+
+pub mod import;
+pub mod prosody;
+
+/// Settings for generating words, independent of where they came from.
+///
+/// Definition files are plain text with one entry per line:
+///
+/// ```text
+/// # Lines starting with # are comments
+/// min-syllables = 1
+/// max-syllables = 3
+/// syllable = CV(C),V
+/// initial-syllable = (s)CV
+/// medial-syllable = CV
+/// final-syllable = CV(N)
+/// filter = #tl a.a
+/// orthography = ʃ>sh; tʃ>ch
+/// stress = penultimate
+/// stress-mark = ipa
+/// tone = high*2,low,˥˩
+/// nucleus = V
+/// notation = x-sampa
+/// V:a*3,e,i,o,u
+/// {Nasal}:m,n
+/// ```
+///
+/// Every line that is not one of the settings above is a category. `filter` and `orthography` can be repeated. With
+/// `notation` set to x-sampa or cxs, the letters of the categories are converted to IPA while reading the file. A letter
+/// like `@stops` or `@front-vowels` stands for those phonemes of the channel's inventory, and `[+voice -continuant]`
+/// for all sounds with those features. Patterns can use feature expressions too, and `@[...]` keeps only the sounds of
+/// the inventory.
+#[derive(Clone, Default)]
+pub struct Definition {
+    pub min_syllables: Option<i64>,
+    pub max_syllables: Option<i64>,
+    pub syllable: Option<String>,
+    pub initial_syllable: Option<String>,
+    pub medial_syllable: Option<String>,
+    pub final_syllable: Option<String>,
+    pub categories: Vec<(String, String)>,
+    pub filter: Option<String>,
+    pub orthography: Option<String>,
+    pub stress: Option<String>,
+    pub stress_mark: Option<String>,
+    pub tone: Option<String>,
+    pub nucleus: Option<String>
+}
+
+impl Definition {
+    pub fn parse_file(content: &str) -> Result<Definition, String> {
+        let mut definition = Definition::default();
+        let mut filters = Vec::new();
+        let mut orthography = Vec::new();
+        let mut notation = None;
+
+        for (idx, line) in content.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            let setting = line.split_once("=").map(|(key, value)| (key.trim(), value.trim().to_string()));
+            match setting {
+                Some(("min-syllables", value)) => definition.min_syllables = Some(parse_line_number(idx, &value)?),
+                Some(("max-syllables", value)) => definition.max_syllables = Some(parse_line_number(idx, &value)?),
+                Some(("syllable", value)) => definition.syllable = Some(value),
+                Some(("initial-syllable", value)) => definition.initial_syllable = Some(value),
+                Some(("medial-syllable", value)) => definition.medial_syllable = Some(value),
+                Some(("final-syllable", value)) => definition.final_syllable = Some(value),
+                Some(("filter", value)) => filters.push(value),
+                Some(("orthography", value)) => orthography.push(value),
+                Some(("stress", value)) => definition.stress = Some(value),
+                Some(("stress-mark", value)) => definition.stress_mark = Some(value),
+                Some(("tone", value)) => definition.tone = Some(value),
+                Some(("nucleus", value)) => definition.nucleus = Some(value),
+                Some(("notation", value)) => notation = Some(Notation::parse(&value)?),
+                _ if line.contains(':') => definition.categories.push((format!("on line {}", idx + 1), line.to_string())),
+                _ => return Err(format!("Line {} is neither a setting nor a category: {line}", idx + 1))
+            }
+        }
+
+        if !filters.is_empty() {
+            definition.filter = Some(filters.join(" "));
+        }
+        if !orthography.is_empty() {
+            definition.orthography = Some(orthography.join("; "));
+        }
+        if let Some(notation) = notation {
+            for (_, category) in definition.categories.iter_mut() {
+                *category = map_letters(category, |letter| Ok(vec![match class_reference(letter).is_some() || letter.contains('[') {
+                    true => letter.to_string(),
+                    false => to_ipa(letter, notation)
+                }]))?;
+            }
+        }
+        Ok(definition)
+    }
+
+    pub fn to_file(&self) -> String {
+        let mut file = String::new();
+        let settings = [
+            ("min-syllables", self.min_syllables.map(|count| count.to_string())),
+            ("max-syllables", self.max_syllables.map(|count| count.to_string())),
+            ("syllable", self.syllable.clone()),
+            ("initial-syllable", self.initial_syllable.clone()),
+            ("medial-syllable", self.medial_syllable.clone()),
+            ("final-syllable", self.final_syllable.clone()),
+            ("filter", self.filter.clone()),
+            ("orthography", self.orthography.clone()),
+            ("stress", self.stress.clone()),
+            ("stress-mark", self.stress_mark.clone()),
+            ("tone", self.tone.clone()),
+            ("nucleus", self.nucleus.clone())
+        ];
+        for (key, value) in settings {
+            if let Some(value) = value {
+                file.push_str(&format!("{key} = {value}\n"));
+            }
+        }
+        for (_, category) in &self.categories {
+            file.push_str(category);
+            file.push('\n');
+        }
+        file
+    }
+
+    pub fn add_categories(&mut self, label: &str, option: &str) {
+        for category in option.split(";").map(str::trim).filter(|category| !category.is_empty()) {
+            self.categories.push((label.to_string(), category.to_string()));
+        }
+    }
+
+    pub fn merge(&mut self, other: Definition) {
+        self.min_syllables = other.min_syllables.or(self.min_syllables);
+        self.max_syllables = other.max_syllables.or(self.max_syllables);
+        self.syllable = other.syllable.or(self.syllable.take());
+        self.initial_syllable = other.initial_syllable.or(self.initial_syllable.take());
+        self.medial_syllable = other.medial_syllable.or(self.medial_syllable.take());
+        self.final_syllable = other.final_syllable.or(self.final_syllable.take());
+        self.categories.extend(other.categories);
+        self.filter = other.filter.or(self.filter.take());
+        self.orthography = other.orthography.or(self.orthography.take());
+        self.stress = other.stress.or(self.stress.take());
+        self.stress_mark = other.stress_mark.or(self.stress_mark.take());
+        self.tone = other.tone.or(self.tone.take());
+        self.nucleus = other.nucleus.or(self.nucleus.take());
+    }
+
+    /// Whether any category or pattern refers to the channel's phoneme inventory, like `@stops` or `@[+nasal]`.
+    pub fn uses_inventory(&self) -> bool {
+        let letters = self.categories.iter()
+            .flat_map(|(_, category)| category.split_once(':').map(|(_, letters)| letters).unwrap_or_default().split(','))
+            .any(|letter| class_reference(letter.rsplit_once('*').map_or(letter, |(letter, _)| letter)).is_some());
+        letters || self.patterns().any(|pattern| pattern.contains("@["))
+            || self.categories.iter().any(|(_, category)| category.contains("@["))
+    }
+
+    fn patterns(&self) -> impl Iterator<Item = &String> {
+        [&self.syllable, &self.initial_syllable, &self.medial_syllable, &self.final_syllable].into_iter().flatten()
+    }
+
+    /// Replaces references like `@stops`, `[+voice -continuant]` or `@[+front]` with the sounds they stand for. In
+    /// patterns, feature expressions become categories named after their features.
+    pub fn expand_classes(&mut self, inventory: Option<&Inventory>) -> Result<(), String> {
+        for (label, category) in self.categories.iter_mut() {
+            *category = map_letters(category, |letter| {
+                let members = match (class_reference(letter), NaturalClass::parse(letter.trim())?) {
+                    (Some(name), _) => inventory.ok_or(MISSING_INVENTORY.to_string())?.class(name)?,
+                    (None, Some(class)) => class.members(inventory)?,
+                    (None, None) => return Ok(vec![letter.to_string()])
+                };
+                match members.is_empty() {
+                    true => Err(format!("{} matches no sounds", letter.trim())),
+                    false => Ok(members.into_iter().map(str::to_string).collect())
+                }
+            }).map_err(|err| format!("Category {label}: {err}"))?;
+        }
+
+        let mut categories = Vec::new();
+        for pattern in [&mut self.syllable, &mut self.initial_syllable, &mut self.medial_syllable, &mut self.final_syllable].into_iter().flatten() {
+            let mut expanded = String::new();
+            let mut copied = 0;
+            for (start, end, class) in find_classes(pattern)? {
+                let name = class.name();
+                if !categories.iter().any(|(_, category): &(String, String)| category.starts_with(&format!("{name}:"))) {
+                    let members = class.members(inventory)?;
+                    if members.is_empty() {
+                        return Err(format!("{} matches no sounds", &pattern[start..end]));
+                    }
+                    categories.push((format!("for {}", &pattern[start..end]), format!("{name}:{}", members.join(","))));
+                }
+                expanded.push_str(&pattern[copied..start]);
+                expanded.push_str(&format!("{{{name}}}"));
+                copied = end;
+            }
+            expanded.push_str(&pattern[copied..]);
+            *pattern = expanded;
+        }
+        self.categories.extend(categories);
+        Ok(())
+    }
+
+    pub fn compile(&self) -> Result<Generator<'_>, String> {
+        let (Some(min_syllables), Some(max_syllables)) = (self.min_syllables, self.max_syllables) else {
+            return Err("Please provide min-syllables and max-syllables".to_string());
+        };
+        if !(1..=50).contains(&min_syllables) || !(1..=50).contains(&max_syllables) {
+            return Err("Syllable counts must be between 1 and 50".to_string());
+        }
+        if min_syllables > max_syllables {
+            return Err("min-syllables can not be larger than max-syllables".to_string());
+        }
+
+        let mut categories = Categories::new();
+        for (label, definition) in &self.categories {
+            let (name, category) = parse_category_definition(label, definition)?;
+            categories.insert(name, category);
+        }
+
+        let parse = |name: &str, pattern: &Option<String>| pattern.as_ref()
+            .map(|pattern| parse_pattern(pattern, &categories).map_err(|err| format!("{name}: {err}")))
+            .transpose();
+        let patterns = SyllablePatterns::new(
+            parse("syllable", &self.syllable)?,
+            parse("initial-syllable", &self.initial_syllable)?,
+            parse("medial-syllable", &self.medial_syllable)?,
+            parse("final-syllable", &self.final_syllable)?
+        )?;
+
+        let filters = match &self.filter {
+            Some(filter) => filter.split_whitespace()
+                .map(|filter| parse_filter(filter, &categories))
+                .collect::<Result<Vec<_>, _>>()?,
+            None => Vec::new()
+        };
+
+        let prosody = Prosody::new(self.stress.as_deref(), self.stress_mark.as_deref(), self.tone.as_deref(),
+            self.nucleus.as_deref(), &categories)?;
+
+        Ok(Generator {
+            categories,
+            patterns,
+            filters,
+            orthography: self.orthography.as_deref().map(Orthography::parse).transpose()?,
+            prosody,
+            min_syllables: min_syllables as usize,
+            max_syllables: max_syllables as usize
+        })
+    }
+}
+
+
+/// Rewrites every letter of a category like `S:s*2,S` with `map`, keeping the name, zipf mode and weights.
+fn map_letters(category: &str, mut map: impl FnMut(&str) -> Result<Vec<String>, String>) -> Result<String, String> {
+    let Some((name, letters)) = category.split_once(':') else {
+        return Ok(category.to_string());
+    };
+    let (zipf, letters) = match letters.strip_prefix("zipf:") {
+        Some(letters) => ("zipf:", letters),
+        None => ("", letters)
+    };
+    let mut mapped = Vec::new();
+    for letter in letters.split(',') {
+        match letter.rsplit_once('*') {
+            Some((letter, weight)) => mapped.extend(map(letter)?.into_iter().map(|letter| format!("{letter}*{weight}"))),
+            None => mapped.extend(map(letter)?)
+        }
+    }
+    Ok(format!("{name}:{zipf}{}", mapped.join(",")))
+}
+
+fn parse_line_number(idx: usize, value: &str) -> Result<i64, String> {
+    value.parse().map_err(|_| format!("Line {} does not contain a valid number: {value}", idx + 1))
+}
+
+pub struct Generator<'d> {
+    pub categories: Categories<'d>,
+    pub patterns: SyllablePatterns,
+    pub filters: Vec<Filter<'d>>,
+    pub orthography: Option<Orthography>,
+    pub prosody: Prosody<'d>,
+    pub min_syllables: usize,
+    pub max_syllables: usize
+}
+
+pub struct Generated<'g> {
+    pub words: Vec<Word<'g>>,
+    pub duplicates: usize,
+    pub rejected: usize,
+    pub repaired: usize
+}
+
+pub enum WordCount {
+    Exact(usize),
+    AtMost(f64)
+}
+
+impl Generator<'_> {
+    pub fn generate<R: Rng + ?Sized>(&self, rng: &mut R, amount: usize, mut progress: impl FnMut(usize)) -> Generated<'_> {
+        let mut seen = HashSet::with_capacity(amount);
+        let mut duplicates = 0;
+        let mut rejected = 0;
+        let mut repaired = 0;
+        let mut words: Vec<Word> = Vec::with_capacity(amount);
+        for _ in 0..amount {
+            for _ in 0..10 {
+                let mut word = generate_word(rng, self.min_syllables, self.max_syllables, &self.patterns, &self.categories, &self.prosody);
+                match apply_filters(&mut word, &self.filters, &self.categories) {
+                    FilterResult::Rejected => {
+                        rejected += 1;
+                        continue;
+                    }
+                    FilterResult::Repaired => repaired += 1,
+                    FilterResult::Passed => {}
+                }
+                if !self.prosody.is_empty() {
+                    self.prosody.render(&mut word);
+                }
+                if seen.insert(word.text.clone()) {
+                    words.push(word);
+                    progress(words.len());
+                    break;
+                }
+                duplicates += 1;
+            }
+        }
+
+        Generated { words, duplicates, rejected, repaired }
+    }
+
+    /// Counts the distinct words the definition can produce. Small spaces are enumerated with filters applied,
+    /// larger ones are bounded by the number of ways to generate a word.
+    pub fn count_words(&self) -> WordCount {
+        let mut paths = 0.0;
+        for count in self.min_syllables..=self.max_syllables {
+            paths += (0..count)
+                .map(|idx| self.patterns.for_position(idx, count).syllables.iter()
+                    .map(|syllable| count_nodes(&syllable.nodes, &self.categories))
+                    .sum::<f64>())
+                .product::<f64>();
+        }
+        if paths > 50_000.0 {
+            return WordCount::AtMost(paths);
+        }
+
+        let mut unique = HashSet::new();
+        for count in self.min_syllables..=self.max_syllables {
+            let mut words = vec![Word { text: String::new(), boundaries: Vec::new(), sources: Vec::new(), tones: Vec::new(), stress: None, unmarked: None }];
+            for idx in 0..count {
+                let pattern = self.patterns.for_position(idx, count);
+                let mut next = Vec::new();
+                for word in &words {
+                    for syllable in &pattern.syllables {
+                        for text in expand_nodes(&syllable.nodes, &self.categories, vec![word.text.clone()]) {
+                            let mut boundaries = word.boundaries.clone();
+                            if idx > 0 {
+                                boundaries.push(word.text.len());
+                            }
+                            let mut sources = word.sources.clone();
+                            sources.push(syllable.source.as_str());
+                            next.push(Word { text, boundaries, sources, tones: Vec::new(), stress: None, unmarked: None });
+                        }
+                    }
+                }
+                words = next;
+            }
+
+            for mut word in words {
+                if !matches!(apply_filters(&mut word, &self.filters, &self.categories), FilterResult::Rejected) {
+                    unique.insert(word.text);
+                }
+            }
+        }
+        WordCount::Exact(unique.len())
+    }
+
+    pub fn phoneme_frequencies(&self, words: &[Word]) -> Vec<(String, usize)> {
+        let mut letters: Vec<&str> = self.categories.values()
+            .flat_map(|category| category.letters.iter().copied())
+            .filter(|letter| !letter.is_empty())
+            .collect();
+        letters.sort_by_key(|letter| std::cmp::Reverse(letter.len()));
+
+        let mut counts: HashMap<String, usize> = HashMap::new();
+        for word in words {
+            let mut rest = word.phonemes();
+            while let Some(char) = rest.chars().next() {
+                let phoneme = letters.iter()
+                    .find(|letter| rest.starts_with(**letter))
+                    .copied()
+                    .unwrap_or(&rest[..char.len_utf8()]);
+                *counts.entry(phoneme.to_string()).or_default() += 1;
+                rest = &rest[phoneme.len()..];
+            }
+        }
+
+        let mut frequencies: Vec<(String, usize)> = counts.into_iter().collect();
+        frequencies.sort_by(|(a, a_count), (b, b_count)| b_count.cmp(a_count).then_with(|| a.cmp(b)));
+        frequencies
+    }
+}
+
+pub struct Pattern {
+    pub syllables: Vec<Syllable>,
+    weights: Option<WeightedIndex<f64>>
+}
+
+impl Pattern {
+    fn pick<R: Rng + ?Sized>(&self, rng: &mut R) -> &Syllable {
+        match &self.weights {
+            Some(weights) => &self.syllables[weights.sample(rng)],
+            None => &self.syllables[rng.random_range(0..self.syllables.len())]
+        }
+    }
+}
+
+pub struct Syllable {
+    pub source: String,
+    pub nodes: Vec<Node>
+}
+
+pub struct SyllablePatterns {
+    generic: Option<Pattern>,
+    initial: Option<Pattern>,
+    medial: Option<Pattern>,
+    last: Option<Pattern>
+}
+
+impl SyllablePatterns {
+    pub fn new(generic: Option<Pattern>, initial: Option<Pattern>, medial: Option<Pattern>, last: Option<Pattern>) -> Result<Self, String> {
+        if generic.is_none() && (initial.is_none() || medial.is_none() || last.is_none()) {
+            return Err("Please provide syllable or an initial, medial and final syllable pattern".to_string());
+        }
+
+        Ok(SyllablePatterns { generic, initial, medial, last })
+    }
+
+    pub fn for_position(&self, idx: usize, count: usize) -> &Pattern {
+        let specific = if count == 1 {
+            self.last.as_ref().or(self.initial.as_ref())
+        } else if idx == 0 {
+            self.initial.as_ref()
+        } else if idx + 1 == count {
+            self.last.as_ref()
+        } else {
+            self.medial.as_ref()
+        };
+        specific.or(self.generic.as_ref()).unwrap()
+    }
+}
+
+pub enum Node {
+    Category(String),
+    Literal(String),
+    Optional(Vec<Node>, Option<f64>),
+    Alternation(Vec<Vec<Node>>, WeightedIndex<f64>),
+    Geminate
+}
+
+struct PatternParser<'p, 'c> {
+    pattern: &'p str,
+    pos: usize,
+    categories: &'c Categories<'c>
+}
+
+impl PatternParser<'_, '_> {
+    fn column(&self, pos: usize) -> usize {
+        self.pattern[..pos].chars().count() + 1
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.pattern[self.pos..].chars().next()
+    }
+
+    fn unexpected(&self) -> String {
+        match self.peek() {
+            Some(char) => format!("Unexpected {char} at column {}", self.column(self.pos)),
+            None => "Unexpected end of pattern".to_string()
+        }
+    }
+
+    fn parse_number(&mut self, prefix: char) -> Result<Option<f64>, String> {
+        if self.peek() != Some(prefix) {
+            return Ok(None);
+        }
+
+        let start = self.pos;
+        self.pos += 1;
+        let length = self.pattern[self.pos..].find(|char: char| !char.is_ascii_digit() && char != '.')
+            .unwrap_or(self.pattern.len() - self.pos);
+        let number = &self.pattern[self.pos..self.pos + length];
+        self.pos += length;
+
+        match number.parse::<f64>() {
+            Ok(number) if number.is_finite() && number >= 0.0 => Ok(Some(number)),
+            _ => Err(format!("Expected a number after the {prefix} at column {}", self.column(start)))
+        }
+    }
+
+    fn parse_weight(&mut self) -> Result<f64, String> {
+        let start = self.pos;
+        match self.parse_number('*')? {
+            Some(weight) if weight <= 0.0 => Err(format!("The weight at column {} must be larger than 0", self.column(start))),
+            weight => Ok(weight.unwrap_or(1.0))
+        }
+    }
+
+    fn parse_sequence(&mut self, literals: bool) -> Result<Vec<Node>, String> {
+        let mut nodes = Vec::new();
+        while let Some(char) = self.peek() {
+            let start = self.pos;
+            match char {
+                ',' | '*' | '|' | ')' | ']' => break,
+                char if char.is_whitespace() => self.pos += char.len_utf8(),
+                '(' => {
+                    self.pos += 1;
+                    let group = self.parse_sequence(literals)?;
+                    match self.peek() {
+                        Some(')') => self.pos += 1,
+                        None => return Err(format!("The ( at column {} is never closed", self.column(start))),
+                        _ => return Err(self.unexpected())
+                    }
+                    if group.is_empty() {
+                        return Err(format!("The group at column {} is empty", self.column(start)));
+                    }
+
+                    let probability_start = self.pos;
+                    let probability = self.parse_number('%')?;
+                    if probability.is_some_and(|probability| probability > 100.0) {
+                        return Err(format!("The probability at column {} can not be larger than 100", self.column(probability_start)));
+                    }
+                    nodes.push(Node::Optional(group, probability));
+                }
+                '[' => {
+                    self.pos += 1;
+                    let mut alternatives = Vec::new();
+                    let mut weights = Vec::new();
+                    loop {
+                        let alternative_start = self.pos;
+                        let alternative = self.parse_sequence(true)?;
+                        if self.peek().is_none() {
+                            return Err(format!("The [ at column {} is never closed", self.column(start)));
+                        }
+                        if alternative.is_empty() {
+                            return Err(format!("Empty alternative at column {}", self.column(alternative_start)));
+                        }
+                        alternatives.push(alternative);
+                        weights.push(self.parse_weight()?);
+
+                        match self.peek() {
+                            Some('|') => self.pos += 1,
+                            Some(']') => break,
+                            None => return Err(format!("The [ at column {} is never closed", self.column(start))),
+                            _ => return Err(self.unexpected())
+                        }
+                    }
+                    self.pos += 1;
+                    nodes.push(Node::Alternation(alternatives, WeightedIndex::new(weights).unwrap()));
+                }
+                '!' => {
+                    self.pos += 1;
+                    nodes.push(Node::Geminate);
+                }
+                char => match category_at(self.categories, &self.pattern[self.pos..]) {
+                    Some((name, len)) => {
+                        self.pos += len;
+                        nodes.push(Node::Category(name.to_string()));
+                    }
+                    None if char == '{' => {
+                        let name = self.pattern[self.pos..].split_once("}").map(|(name, _)| &name[1..]);
+                        return match name {
+                            Some(name) => Err(format!("Category not defined: {name} (column {})", self.column(start))),
+                            None => Err(format!("The {{ at column {} is never closed", self.column(start)))
+                        };
+                    }
+                    None if literals => {
+                        self.pos += char.len_utf8();
+                        nodes.push(Node::Literal(char.to_string()));
+                    }
+                    None => return Err(format!("Category not defined: {char} (column {})", self.column(start)))
+                }
+            }
+        }
+        Ok(nodes)
+    }
+}
+
+pub fn parse_pattern(pattern: &str, categories: &Categories) -> Result<Pattern, String> {
+    let mut parser = PatternParser { pattern, pos: 0, categories };
+    let mut syllables = Vec::new();
+    let mut weights = Vec::new();
+
+    loop {
+        let start = parser.pos;
+        let nodes = parser.parse_sequence(false)?;
+        if nodes.is_empty() {
+            return Err(format!("Empty syllable at column {}", parser.column(start)));
+        }
+        syllables.push(Syllable { source: pattern[start..parser.pos].trim().to_string(), nodes });
+        weights.push(parser.parse_weight()?);
+
+        match parser.peek() {
+            Some(',') => parser.pos += 1,
+            Some(')') => return Err(format!("The ) at column {} has no matching (", parser.column(parser.pos))),
+            Some(']') => return Err(format!("The ] at column {} has no matching [", parser.column(parser.pos))),
+            Some(_) => return Err(parser.unexpected()),
+            None => break
+        }
+    }
+
+    let weights = if weights.iter().all(|weight| *weight == 1.0) {
+        None
+    } else {
+        Some(WeightedIndex::new(weights).unwrap())
+    };
+    Ok(Pattern { syllables, weights })
+}
+
+pub enum FilterToken {
+    WordStart,
+    WordEnd,
+    SyllableBoundary,
+    Letter(char),
+    Category(String)
+}
+
+pub struct Filter<'a> {
+    pub tokens: Vec<FilterToken>,
+    pub repair: Option<&'a str>
+}
+
+pub enum FilterResult {
+    Passed,
+    Repaired,
+    Rejected
+}
+
+pub fn parse_filter<'a>(filter: &'a str, categories: &Categories) -> Result<Filter<'a>, String> {
+    let (sequence, repair) = match filter.split_once(">") {
+        Some((sequence, repair)) => (sequence, Some(repair)),
+        None => (filter, None)
+    };
+
+    let mut tokens = Vec::new();
+    let mut rest = sequence;
+    while let Some(char) = rest.chars().next() {
+        if let Some((name, len)) = category_at(categories, rest) {
+            tokens.push(FilterToken::Category(name.to_string()));
+            rest = &rest[len..];
+            continue;
+        }
+
+        tokens.push(match char {
+            '#' if tokens.is_empty() => FilterToken::WordStart,
+            '#' if rest.len() == 1 => FilterToken::WordEnd,
+            '#' => return Err(format!("Filter {filter} can only use # at the start or end")),
+            '.' => FilterToken::SyllableBoundary,
+            char => FilterToken::Letter(char)
+        });
+        rest = &rest[char.len_utf8()..];
+    }
+
+    if !tokens.iter().any(|token| matches!(token, FilterToken::Letter(_) | FilterToken::Category(_))) {
+        return Err(format!("Filter {filter} does not contain any letters"));
+    }
+
+    Ok(Filter { tokens, repair })
+}
+
+pub struct Word<'p> {
+    pub text: String,
+    pub boundaries: Vec<usize>,
+    pub sources: Vec<&'p str>,
+    pub tones: Vec<Option<&'p str>>,
+    pub stress: Option<usize>,
+    /// The text before stress and tone marks were added, if there are any.
+    pub unmarked: Option<String>
+}
+
+impl Word<'_> {
+    /// The word without stress and tone marks.
+    pub fn phonemes(&self) -> &str {
+        self.unmarked.as_deref().unwrap_or(&self.text)
+    }
+
+    pub fn syllables(&self) -> Vec<&str> {
+        let mut start = 0;
+        let mut syllables = Vec::with_capacity(self.boundaries.len() + 1);
+        for boundary in &self.boundaries {
+            syllables.push(&self.text[start..*boundary]);
+            start = *boundary;
+        }
+        syllables.push(&self.text[start..]);
+        syllables
+    }
+
+    fn find(&self, tokens: &[FilterToken], categories: &Categories) -> Option<(usize, usize)> {
+        (0..=self.text.len())
+            .filter(|start| self.text.is_char_boundary(*start))
+            .find_map(|start| self.match_at(tokens, start, categories).map(|end| (start, end)))
+    }
+
+    fn match_at(&self, tokens: &[FilterToken], pos: usize, categories: &Categories) -> Option<usize> {
+        let Some((token, rest)) = tokens.split_first() else {
+            return Some(pos);
+        };
+
+        match token {
+            FilterToken::WordStart if pos == 0 => self.match_at(rest, pos, categories),
+            FilterToken::WordEnd if pos == self.text.len() => self.match_at(rest, pos, categories),
+            FilterToken::SyllableBoundary if self.boundaries.contains(&pos) => self.match_at(rest, pos, categories),
+            FilterToken::Letter(char) if self.text[pos..].starts_with(*char) => self.match_at(rest, pos + char.len_utf8(), categories),
+            FilterToken::Category(name) => categories[name].letters.iter()
+                .filter(|letter| !letter.is_empty() && self.text[pos..].starts_with(*letter))
+                .find_map(|letter| self.match_at(rest, pos + letter.len(), categories)),
+            _ => None
+        }
+    }
+
+    fn replace(&mut self, start: usize, end: usize, replacement: &str) {
+        self.text.replace_range(start..end, replacement);
+        let kept: Vec<bool> = (0..self.sources.len())
+            .map(|idx| idx == 0 || self.boundaries[idx - 1] <= start || self.boundaries[idx - 1] >= end)
+            .collect();
+        let mut keep = kept.iter();
+        self.sources.retain(|_| *keep.next().unwrap());
+        let mut keep = kept.iter();
+        self.tones.retain(|_| *keep.next().unwrap_or(&true));
+        if let Some(stress) = self.stress {
+            self.stress = Some(kept[..=stress].iter().filter(|kept| **kept).count() - 1);
+        }
+        self.boundaries.retain(|boundary| *boundary <= start || *boundary >= end);
+        for boundary in &mut self.boundaries {
+            if *boundary >= end {
+                *boundary = *boundary + replacement.len() - (end - start);
+            }
+        }
+    }
+}
+
+pub fn apply_filters(word: &mut Word, filters: &[Filter], categories: &Categories) -> FilterResult {
+    let mut result = FilterResult::Passed;
+    for filter in filters {
+        let mut repairs = 0;
+        while let Some((start, end)) = word.find(&filter.tokens, categories) {
+            match filter.repair {
+                Some(repair) if repairs < 10 => {
+                    word.replace(start, end, repair);
+                    repairs += 1;
+                    result = FilterResult::Repaired;
+                }
+                _ => return FilterResult::Rejected
+            }
+        }
+    }
+    result
+}
+
+pub fn generate_word<'p, R: Rng + ?Sized>(rng: &mut R, min_syllables: usize, max_syllables: usize,
+                                          patterns: &'p SyllablePatterns, categories: &Categories, prosody: &'p Prosody) -> Word<'p> {
+    let mut word = String::new();
+    let mut boundaries = Vec::new();
+    let mut sources = Vec::new();
+    let mut tones = Vec::new();
+    let syllable_count = rng.random_range(min_syllables..=max_syllables);
+
+    for idx in 0..syllable_count {
+        if idx > 0 {
+            boundaries.push(word.len());
+        }
+        let pattern = patterns.for_position(idx, syllable_count);
+        let syllable = pattern.pick(rng);
+        generate_nodes(rng, &syllable.nodes, categories, &mut word);
+        sources.push(syllable.source.as_str());
+        tones.push(prosody.pick_tone(rng));
+    }
+
+    let mut word = Word { text: word, boundaries, sources, tones, stress: None, unmarked: None };
+    if !prosody.is_empty() {
+        let stress = prosody.pick_stress(rng, &word.syllables());
+        word.stress = stress;
+    }
+    word
+}
+
+fn count_nodes(nodes: &[Node], categories: &Categories) -> f64 {
+    nodes.iter().map(|node| match node {
+        Node::Category(name) => categories[name].letters.len() as f64,
+        Node::Literal(_) | Node::Geminate => 1.0,
+        Node::Optional(_, Some(probability)) if *probability == 0.0 => 1.0,
+        Node::Optional(group, Some(probability)) if *probability == 100.0 => count_nodes(group, categories),
+        Node::Optional(group, _) => count_nodes(group, categories) + 1.0,
+        Node::Alternation(alternatives, _) => alternatives.iter()
+            .map(|alternative| count_nodes(alternative, categories))
+            .sum()
+    }).product()
+}
+
+pub(crate) fn expand_nodes(nodes: &[Node], categories: &Categories, mut texts: Vec<String>) -> Vec<String> {
+    for node in nodes {
+        texts = match node {
+            Node::Category(name) => texts.iter()
+                .flat_map(|text| categories[name].letters.iter().map(move |letter| format!("{text}{letter}")))
+                .collect(),
+            Node::Literal(letter) => texts.into_iter().map(|text| text + letter).collect(),
+            Node::Optional(_, Some(probability)) if *probability == 0.0 => texts,
+            Node::Optional(group, Some(probability)) if *probability == 100.0 => expand_nodes(group, categories, texts),
+            Node::Optional(group, _) => {
+                let mut expanded = expand_nodes(group, categories, texts.clone());
+                expanded.extend(texts);
+                expanded
+            }
+            Node::Alternation(alternatives, _) => alternatives.iter()
+                .flat_map(|alternative| expand_nodes(alternative, categories, texts.clone()))
+                .collect(),
+            Node::Geminate => texts.into_iter().map(|mut text| {
+                if let Some(last) = text.chars().last() {
+                    text.push(last);
+                }
+                text
+            }).collect()
+        };
+    }
+    texts
+}
+
+fn generate_nodes<R: Rng + ?Sized>(rng: &mut R, nodes: &[Node], categories: &Categories, word: &mut String) {
+    for node in nodes {
+        match node {
+            Node::Category(name) => word.push_str(categories[name].pick(rng)),
+            Node::Literal(letter) => word.push_str(letter),
+            Node::Optional(group, probability) => {
+                let included = match probability {
+                    Some(probability) => rng.random_bool(probability / 100.0),
+                    None => rng.random()
+                };
+                if included {
+                    generate_nodes(rng, group, categories, word);
+                }
+            }
+            Node::Alternation(alternatives, weights) => {
+                let alternative = &alternatives[weights.sample(rng)];
+                generate_nodes(rng, alternative, categories, word);
+            }
+            Node::Geminate => if let Some(last) = word.chars().last() {
+                word.push(last);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::SeedableRng;
+    use rand::rngs::Xoshiro256PlusPlus;
+    use crate::phonemes::parse_category_option;
+    use super::*;
+
+    fn definition(file: &str) -> Definition {
+        Definition::parse_file(file).unwrap()
+    }
+
+    fn generate(definition: &Definition, seed: u64, amount: usize) -> Vec<String> {
+        let generator = definition.compile().unwrap();
+        let mut rng = Xoshiro256PlusPlus::seed_from_u64(seed);
+        generator.generate(&mut rng, amount, |_| {}).words.into_iter().map(|word| word.text).collect()
+    }
+
+    fn word(syllables: &[&str]) -> Word<'static> {
+        let mut text = String::new();
+        let mut boundaries = Vec::new();
+        for (idx, syllable) in syllables.iter().enumerate() {
+            if idx > 0 {
+                boundaries.push(text.len());
+            }
+            text.push_str(syllable);
+        }
+        Word { text, boundaries, sources: vec![""; syllables.len()], tones: Vec::new(), stress: None, unmarked: None }
+    }
+
+    fn filter(filter: &str, syllables: &[&str]) -> (&'static str, String) {
+        let definition = definition(&format!("min-syllables = 1\nmax-syllables = 1\nsyllable = CV\nC:p,t,k,l\nV:a,e\nfilter = {filter}"));
+        let generator = definition.compile().unwrap();
+        let mut word = word(syllables);
+        let result = match apply_filters(&mut word, &generator.filters, &generator.categories) {
+            FilterResult::Passed => "passed",
+            FilterResult::Repaired => "repaired",
+            FilterResult::Rejected => "rejected"
+        };
+        (result, word.syllables().join("."))
+    }
+
+    fn pattern_error(pattern: &str) -> String {
+        let mut categories = Categories::new();
+        parse_category_option("1", "C:p,t,k;V:a,i", &mut categories).unwrap();
+        match parse_pattern(pattern, &categories) {
+            Ok(_) => panic!("{pattern} should not parse"),
+            Err(err) => err
+        }
+    }
+
+    #[test]
+    fn same_seed_generates_same_words() {
+        let definition = definition("min-syllables = 1\nmax-syllables = 3\nsyllable = CV(C),V\nC:p,t,k,m,n,s\nV:a*3,e,i,o,u");
+        let words = generate(&definition, 1234, 8);
+        assert_eq!(words, generate(&definition, 1234, 8));
+        assert_eq!(words, ["saksua", "mam", "a", "aae", "aa", "esasa", "pina", "soko"]);
+        assert_ne!(words, generate(&definition, 4321, 8));
+    }
+
+    #[test]
+    fn filter_anchors() {
+        assert_eq!(filter("#tl", &["tla"]), ("rejected", "tla".to_string()));
+        assert_eq!(filter("#tl", &["a", "tla"]), ("passed", "a.tla".to_string()));
+        assert_eq!(filter("a#", &["ta", "ka"]), ("rejected", "ta.ka".to_string()));
+        assert_eq!(filter("a#", &["ka", "te"]), ("passed", "ka.te".to_string()));
+        assert_eq!(filter("a.a", &["ka", "ap"]), ("rejected", "ka.ap".to_string()));
+        assert_eq!(filter("a.a", &["kaa", "p"]), ("passed", "kaa.p".to_string()));
+        assert_eq!(filter("C.C", &["tap", "ke"]), ("rejected", "tap.ke".to_string()));
+    }
+
+    #[test]
+    fn filter_repairs() {
+        assert_eq!(filter("tl>tel", &["at", "la"]), ("repaired", "atela".to_string()));
+        assert_eq!(filter("tl>tel", &["atla"]), ("repaired", "atela".to_string()));
+        assert_eq!(filter("tl>tel", &["ka"]), ("passed", "ka".to_string()));
+    }
+
+    #[test]
+    fn pattern_errors_point_at_columns() {
+        assert_eq!(pattern_error("C(V"), "The ( at column 2 is never closed");
+        assert_eq!(pattern_error("CV)"), "The ) at column 3 has no matching (");
+        assert_eq!(pattern_error("CV]"), "The ] at column 3 has no matching [");
+        assert_eq!(pattern_error("CX"), "Category not defined: X (column 2)");
+        assert_eq!(pattern_error("Vʃʃ"), "Category not defined: ʃ (column 2)");
+        assert_eq!(pattern_error("ʃ[C|V"), "Category not defined: ʃ (column 1)");
+        assert_eq!(pattern_error("C[V|"), "The [ at column 2 is never closed");
+        assert_eq!(pattern_error("CV,,V"), "Empty syllable at column 4");
+        assert_eq!(pattern_error("CV*0"), "The weight at column 3 must be larger than 0");
+        assert_eq!(pattern_error("C(V)%150"), "The probability at column 5 can not be larger than 100");
+        assert_eq!(pattern_error("{Nasal}V"), "Category not defined: Nasal (column 1)");
+    }
+
+    #[test]
+    fn final_pattern_applies_to_one_syllable_words() {
+        let definition = definition("min-syllables = 1\nmax-syllables = 1\nsyllable = CV\nfinal-syllable = CVN\nC:p,t,k\nV:a,i\nN:n");
+        let words = generate(&definition, 7, 20);
+        assert!(!words.is_empty());
+        assert!(words.iter().all(|word| word.chars().count() == 3 && word.ends_with('n')), "{words:?}");
+    }
+
+    #[test]
+    fn patterns_by_position() {
+        let definition = definition("min-syllables = 1\nmax-syllables = 3\nsyllable = V\ninitial-syllable = CV\nmedial-syllable = VC\nC:p\nV:a");
+        let generator = definition.compile().unwrap();
+        let source = |idx, count| generator.patterns.for_position(idx, count).syllables[0].source.as_str();
+        assert_eq!(source(0, 1), "CV");
+        assert_eq!(source(0, 3), "CV");
+        assert_eq!(source(1, 3), "VC");
+        assert_eq!(source(2, 3), "V");
+        assert_eq!(source(1, 2), "V");
+    }
+
+    #[test]
+    fn literal_brackets_survive_class_expansion() {
+        let mut literal = definition("min-syllables = 1\nmax-syllables = 1\nsyllable = C[hi]\nC:p,t");
+        literal.expand_classes(None).unwrap();
+        assert_eq!(literal.syllable.as_deref(), Some("C[hi]"));
+        assert_eq!(literal.categories.len(), 1);
+        assert!(generate(&literal, 3, 2).iter().all(|word| word.ends_with("hi")));
+
+        let mut features = definition("min-syllables = 1\nmax-syllables = 1\nsyllable = C[+hi -back]\nC:p,t");
+        features.expand_classes(None).unwrap();
+        assert_eq!(features.syllable.as_deref(), Some("C{+high-back}"));
+        let (_, class) = features.categories.iter().find(|(_, category)| category.starts_with("+high-back:")).unwrap();
+        let letters: Vec<&str> = class["+high-back:".len()..].split(',').collect();
+        assert!(letters.contains(&"i") && !letters.contains(&"u"), "{letters:?}");
+    }
+
+    #[test]
+    fn count_words_exact() {
+        let count = |file: &str| match definition(file).compile().unwrap().count_words() {
+            WordCount::Exact(count) => count,
+            WordCount::AtMost(count) => panic!("{file} should be counted exactly, not at most {count}")
+        };
+        assert_eq!(count("min-syllables = 1\nmax-syllables = 2\nsyllable = CV\nC:p,t\nV:a,i"), 4 + 16);
+        assert_eq!(count("min-syllables = 1\nmax-syllables = 2\nsyllable = CV\nC:p,t\nV:a,i\nfilter = a.p"), 4 + 12);
+        assert_eq!(count("min-syllables = 1\nmax-syllables = 2\nsyllable = V,VV\nV:a"), 4);
+        assert_eq!(count("min-syllables = 1\nmax-syllables = 1\nsyllable = C(V)\nC:p,t\nV:a,i"), 6);
+    }
+
+    #[test]
+    fn count_words_bound() {
+        let definition = definition("min-syllables = 1\nmax-syllables = 2\nsyllable = CVC\nC:p,t,k,b,d,g,m,n,s,l\nV:a,e,i,o,u,y,ä,ö,ü,ə");
+        match definition.compile().unwrap().count_words() {
+            WordCount::AtMost(count) => assert_eq!(count, 1000.0 + 1000.0 * 1000.0),
+            WordCount::Exact(count) => panic!("Expected an upper bound, got exactly {count}")
+        }
+    }
+}
@@ -0,0 +1,164 @@
+use rand::{Rng, RngExt};
+use crate::phonemes::{parse_category, Categories, PhonemeCategory};
+use super::Word;
+
+const VOWELS: [&str; 30] = ["a", "e", "i", "o", "u", "y", "æ", "ɑ", "ɐ", "ɒ", "ə", "ɛ", "ɜ", "ɞ", "ɘ", "ɵ", "ɤ", "ɯ", "ɨ", "ʉ",
+    "ɪ", "ʏ", "ʊ", "ø", "œ", "ɶ", "ɔ", "ʌ", "ɚ", "ɝ"];
+
+pub enum Stress {
+    Initial,
+    Final,
+    Penultimate,
+    Antepenultimate,
+    Weight,
+    Random
+}
+
+/// Stress and tone for generated words.
+///
+/// Tones named high, mid, low, rising or falling become diacritics on the first letter of the nucleus, any other tone
+/// (like ˥˩ or ⁵¹) is written after the syllable. The nucleus is the first run of letters from the `nucleus`
+/// categories, which defaults to V or, without a V category, common IPA vowels.
+pub struct Prosody<'d> {
+    stress: Option<Stress>,
+    acute: bool,
+    tones: Option<PhonemeCategory<'d>>,
+    nuclei: Vec<&'d str>
+}
+
+impl<'d> Prosody<'d> {
+    pub fn new(stress: Option<&str>, mark: Option<&str>, tone: Option<&'d str>, nucleus: Option<&str>,
+               categories: &Categories<'d>) -> Result<Self, String> {
+        let stress = match stress.map(str::trim) {
+            None => None,
+            Some("initial") => Some(Stress::Initial),
+            Some("final") => Some(Stress::Final),
+            Some("penultimate") => Some(Stress::Penultimate),
+            Some("antepenultimate") => Some(Stress::Antepenultimate),
+            Some("weight") => Some(Stress::Weight),
+            Some("random") => Some(Stress::Random),
+            Some(stress) => return Err(format!("Unknown stress rule: {stress}. Use initial, final, penultimate, antepenultimate, weight or random"))
+        };
+        let acute = match mark.map(str::trim) {
+            None | Some("ipa") => false,
+            Some("acute") => true,
+            Some(mark) => return Err(format!("Unknown stress mark: {mark}. Use ipa or acute"))
+        };
+        let tones = tone.map(|tone| parse_category("tone", tone.trim())).transpose()?;
+
+        let mut nuclei = Vec::new();
+        match nucleus {
+            Some(nucleus) => for name in nucleus.split(',').map(str::trim) {
+                match categories.get(name) {
+                    Some(category) => nuclei.extend(category.letters.iter().copied()),
+                    None => return Err(format!("nucleus: Category not defined: {name}"))
+                }
+            },
+            None => match categories.get("V") {
+                Some(category) => nuclei.extend(category.letters.iter().copied()),
+                None => nuclei.extend(VOWELS)
+            }
+        }
+        nuclei.retain(|nucleus| !nucleus.is_empty());
+        nuclei.sort_by_key(|nucleus| std::cmp::Reverse(nucleus.len()));
+
+        Ok(Prosody { stress, acute, tones, nuclei })
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.stress.is_none() && self.tones.is_none()
+    }
+
+    pub fn pick_tone<R: Rng + ?Sized>(&self, rng: &mut R) -> Option<&'d str> {
+        self.tones.as_ref().map(|tones| tones.pick(rng))
+    }
+
+    pub fn pick_stress<R: Rng + ?Sized>(&self, rng: &mut R, syllables: &[&str]) -> Option<usize> {
+        let count = syllables.len();
+        match self.stress.as_ref()? {
+            Stress::Initial => Some(0),
+            Stress::Final => Some(count - 1),
+            Stress::Penultimate => Some(count.saturating_sub(2)),
+            Stress::Antepenultimate => Some(count.saturating_sub(3)),
+            Stress::Weight if count >= 3 && !self.is_heavy(syllables[count - 2]) => Some(count - 3),
+            Stress::Weight => Some(count.saturating_sub(2)),
+            Stress::Random => Some(rng.random_range(0..count))
+        }
+    }
+
+    fn nucleus(&self, syllable: &str) -> Option<(usize, usize)> {
+        let start = (0..syllable.len())
+            .filter(|start| syllable.is_char_boundary(*start))
+            .find(|start| self.nuclei.iter().any(|nucleus| syllable[*start..].starts_with(nucleus)))?;
+
+        let mut end = start;
+        while let Some(nucleus) = self.nuclei.iter().find(|nucleus| syllable[end..].starts_with(**nucleus)) {
+            end += nucleus.len();
+        }
+        while syllable[end..].starts_with('ː') {
+            end += 'ː'.len_utf8();
+        }
+        Some((start, end))
+    }
+
+    /// Heavy syllables have a long vowel, a diphthong or a coda.
+    fn is_heavy(&self, syllable: &str) -> bool {
+        match self.nucleus(syllable) {
+            Some((start, end)) => end < syllable.len() || syllable[start..end].chars().count() > 1,
+            None => false
+        }
+    }
+
+    pub fn render(&self, word: &mut Word) {
+        let count = word.boundaries.len() + 1;
+        let mut text = String::with_capacity(word.text.len() + count * 4);
+        let mut boundaries = Vec::with_capacity(word.boundaries.len());
+
+        for (idx, syllable) in word.syllables().into_iter().enumerate() {
+            if idx > 0 {
+                boundaries.push(text.len());
+            }
+            let stressed = count > 1 && word.stress == Some(idx);
+            let tone = word.tones.get(idx).copied().flatten();
+            let nucleus = self.nucleus(syllable).map(|(start, _)| start + syllable[start..].chars().next().unwrap().len_utf8());
+
+            if stressed && !self.acute {
+                text.push('ˈ');
+            }
+            let mut diacritics = String::new();
+            if stressed && self.acute {
+                diacritics.push('\u{301}');
+            }
+            let tone_mark = tone.and_then(tone_diacritic);
+            if let Some(mark) = tone_mark {
+                diacritics.push(mark);
+            }
+
+            match nucleus {
+                Some(split) => {
+                    text.push_str(&syllable[..split]);
+                    text.push_str(&diacritics);
+                    text.push_str(&syllable[split..]);
+                }
+                None => text.push_str(syllable)
+            }
+            if let (Some(tone), None) = (tone, tone_mark) {
+                text.push_str(tone);
+            }
+        }
+
+        word.unmarked = Some(std::mem::replace(&mut word.text, text));
+        word.boundaries = boundaries;
+    }
+}
+
+fn tone_diacritic(tone: &str) -> Option<char> {
+    match tone {
+        "high" => Some('\u{301}'),
+        "mid" => Some('\u{304}'),
+        "low" => Some('\u{300}'),
+        "rising" => Some('\u{30C}'),
+        "falling" => Some('\u{302}'),
+        _ => None
+    }
+}
@@ -0,0 +1,162 @@
+use unicode_normalization::UnicodeNormalization;
+
+/// ASCII notations for IPA. Conversion matches the longest symbol at every position and leaves everything else as it
+/// is, so IPA and spaces pass through unchanged. Where several symbols share an IPA value, the first one listed is
+/// used when converting back.
+#[derive(Clone, Copy)]
+pub enum Notation {
+    XSampa,
+    /// Conlang X-SAMPA, which marks stress with `'` and `,` like IPA and adds a few symbols.
+    Cxs
+}
+
+impl Notation {
+    pub fn parse(notation: &str) -> Result<Self, String> {
+        match notation.trim().to_lowercase().as_str() {
+            "x-sampa" | "xsampa" => Ok(Notation::XSampa),
+            "cxs" => Ok(Notation::Cxs),
+            notation => Err(format!("Unknown notation: {notation}. Use x-sampa or cxs"))
+        }
+    }
+
+    fn symbols(self) -> impl Iterator<Item = &'static (&'static str, &'static str)> {
+        let overrides: &[(&str, &str)] = match self {
+            Notation::XSampa => &[],
+            Notation::Cxs => CXS
+        };
+        overrides.iter().chain(X_SAMPA.iter().filter(move |(ascii, _)| !overrides.iter().any(|(other, _)| other == ascii)))
+    }
+}
+
+pub fn to_ipa(text: &str, notation: Notation) -> String {
+    convert(text, notation.symbols().map(|(ascii, ipa)| (*ascii, *ipa)).collect()).nfc().collect()
+}
+
+pub fn from_ipa(text: &str, notation: Notation) -> String {
+    let mut symbols: Vec<(&str, &str)> = Vec::new();
+    for (ascii, ipa) in notation.symbols() {
+        if !symbols.iter().any(|(other, _)| other == ipa) {
+            symbols.push((ipa, ascii));
+        }
+    }
+    convert(&text.nfd().collect::<String>(), symbols)
+}
+
+fn convert(text: &str, mut symbols: Vec<(&str, &str)>) -> String {
+    symbols.sort_by_key(|(from, _)| std::cmp::Reverse(from.len()));
+    let mut result = String::with_capacity(text.len() * 2);
+    let mut rest = text;
+    while let Some(char) = rest.chars().next() {
+        match symbols.iter().find(|(from, _)| rest.starts_with(from)) {
+            Some((from, to)) => {
+                result.push_str(to);
+                rest = &rest[from.len()..];
+            }
+            None => {
+                result.push(char);
+                rest = &rest[char.len_utf8()..];
+            }
+        }
+    }
+    result
+}
+
+const CXS: &[(&str, &str)] = &[
+    ("'", "ˈ"),
+    (",", "ˌ"),
+    ("b\\", "ⱱ")
+];
+
+const X_SAMPA: &[(&str, &str)] = &[
+    // Consonants
+    ("b_<", "ɓ"), ("d`", "ɖ"), ("d_<", "ɗ"), ("g", "ɡ"), ("g_<", "ɠ"), ("h\\", "ɦ"), ("j\\", "ʝ"), ("l`", "ɭ"),
+    ("l\\", "ɺ"), ("n`", "ɳ"), ("p\\", "ɸ"), ("r`", "ɽ"), ("r\\", "ɹ"), ("r\\`", "ɻ"), ("s`", "ʂ"), ("s\\", "ɕ"),
+    ("t`", "ʈ"), ("v\\", "ʋ"), ("P", "ʋ"), ("x\\", "ɧ"), ("z`", "ʐ"), ("z\\", "ʑ"), ("B", "β"), ("B\\", "ʙ"),
+    ("C", "ç"), ("D", "ð"), ("F", "ɱ"), ("G", "ɣ"), ("G\\", "ɢ"), ("G\\_<", "ʛ"), ("H", "ɥ"), ("H\\", "ʜ"),
+    ("J", "ɲ"), ("J\\", "ɟ"), ("J\\_<", "ʄ"), ("K", "ɬ"), ("K\\", "ɮ"), ("L", "ʎ"), ("L\\", "ʟ"), ("M\\", "ɰ"),
+    ("N", "ŋ"), ("N\\", "ɴ"), ("O\\", "ʘ"), ("R", "ʁ"), ("R\\", "ʀ"), ("S", "ʃ"), ("T", "θ"), ("W", "ʍ"),
+    ("X", "χ"), ("X\\", "ħ"), ("Z", "ʒ"), ("4", "ɾ"), ("5", "ɫ"), ("?", "ʔ"), ("?\\", "ʕ"), ("<\\", "ʢ"),
+    (">\\", "ʡ"), ("!\\", "ǃ"), ("|\\", "ǀ"), ("|\\|\\", "ǁ"), ("=\\", "ǂ"),
+    // Vowels
+    ("A", "ɑ"), ("E", "ɛ"), ("I", "ɪ"), ("I\\", "ᵻ"), ("M", "ɯ"), ("O", "ɔ"), ("Q", "ɒ"), ("U", "ʊ"),
+    ("U\\", "ᵿ"), ("V", "ʌ"), ("Y", "ʏ"), ("@", "ə"), ("@\\", "ɘ"), ("@`", "ɚ"), ("{", "æ"), ("}", "ʉ"),
+    ("1", "ɨ"), ("2", "ø"), ("3", "ɜ"), ("3\\", "ɞ"), ("3`", "ɝ"), ("6", "ɐ"), ("7", "ɤ"), ("8", "ɵ"),
+    ("9", "œ"), ("&", "ɶ"),
+    // Suprasegmentals
+    ("\"", "ˈ"), ("%", "ˌ"), (":", "ː"), (":\\", "ˑ"), ("_X", "\u{306}"), ("-\\", "‿"), ("||", "‖"), ("^", "ꜛ"),
+    ("!", "ꜜ"), ("<R>", "↗"), ("<F>", "↘"),
+    // Tones
+    ("_T", "\u{30B}"), ("_H", "\u{301}"), ("_M", "\u{304}"), ("_L", "\u{300}"), ("_B", "\u{30F}"), ("_R", "\u{30C}"),
+    ("_F", "\u{302}"), ("_H_T", "\u{1DC4}"), ("_B_L", "\u{1DC5}"), ("_R_F", "\u{1DC8}"),
+    ("_T", "˥"), ("_H", "˦"), ("_M", "˧"), ("_L", "˨"), ("_B", "˩"),
+    // Diacritics
+    ("_h", "ʰ"), ("_j", "ʲ"), ("'", "ʲ"), ("_w", "ʷ"), ("_G", "ˠ"), ("_?\\", "ˤ"), ("_n", "ⁿ"), ("_l", "ˡ"),
+    ("_>", "ʼ"), ("~", "\u{303}"), ("_~", "\u{303}"), ("=", "\u{329}"), ("_=", "\u{329}"), ("_0", "\u{325}"),
+    ("_v", "\u{32C}"), ("_t", "\u{324}"), ("_k", "\u{330}"), ("_N", "\u{33C}"), ("_d", "\u{32A}"), ("_a", "\u{33A}"),
+    ("_m", "\u{33B}"), ("_^", "\u{32F}"), ("_}", "\u{31A}"), ("_O", "\u{339}"), ("_c", "\u{31C}"), ("_+", "\u{31F}"),
+    ("_-", "\u{320}"), ("_\"", "\u{308}"), ("_x", "\u{33D}"), ("_r", "\u{31D}"), ("_o", "\u{31E}"), ("_A", "\u{318}"),
+    ("_q", "\u{319}"), ("_e", "\u{334}"), ("`", "\u{2DE}")
+];
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn longest_symbols_win() {
+        assert_eq!(to_ipa("t_hEst", Notation::XSampa), "tʰɛst");
+        assert_eq!(to_ipa("@ @\\ @`", Notation::XSampa), "ə ɘ ɚ");
+        assert_eq!(to_ipa("r r\\ r\\`", Notation::XSampa), "r ɹ ɻ");
+        assert_eq!(to_ipa("|\\|\\ || |\\", Notation::XSampa), "ǁ ‖ ǀ");
+        assert_eq!(to_ipa("J\\_<a", Notation::XSampa), "ʄa");
+    }
+
+    #[test]
+    fn diacritics_combine_with_the_letter_before() {
+        assert_eq!(to_ipa("n_0", Notation::XSampa), "n\u{325}");
+        assert_eq!(to_ipa("a_H", Notation::XSampa), "á");
+        assert_eq!(to_ipa("e~", Notation::XSampa), "ẽ");
+        assert_eq!(to_ipa("k_w_h", Notation::XSampa), "kʷʰ");
+        assert_eq!(to_ipa("a_H_T", Notation::XSampa), "a\u{1DC4}");
+    }
+
+    #[test]
+    fn ipa_and_unknown_text_pass_through() {
+        assert_eq!(to_ipa("ʃa b\\ [", Notation::XSampa), "ʃa b\\ [");
+        assert_eq!(from_ipa("sa [tʃ]", Notation::XSampa), "sa [tS]");
+    }
+
+    #[test]
+    fn from_ipa_uses_the_first_symbol() {
+        assert_eq!(from_ipa("tʰɛst", Notation::XSampa), "t_hEst");
+        assert_eq!(from_ipa("ʋ ʲ", Notation::XSampa), "v\\ _j");
+        assert_eq!(from_ipa("á n\u{325}", Notation::XSampa), "a_H n_0");
+        assert_eq!(from_ipa("ẽ", Notation::XSampa), "e~");
+        assert_eq!(from_ipa("˥˩", Notation::XSampa), "_T_B");
+    }
+
+    #[test]
+    fn round_trip() {
+        for text in ["\"t_hEst", "p\\i:_L", "?\\a%ma_R", "s`u:r\\`", "n_0a~"] {
+            assert_eq!(from_ipa(&to_ipa(text, Notation::XSampa), Notation::XSampa), text);
+        }
+    }
+
+    #[test]
+    fn cxs_differences() {
+        assert_eq!(to_ipa("'ka,ta", Notation::XSampa), "ʲka,ta");
+        assert_eq!(to_ipa("'ka,ta", Notation::Cxs), "ˈkaˌta");
+        assert_eq!(to_ipa("b\\", Notation::Cxs), "ⱱ");
+        assert_eq!(to_ipa("\"ka%ta", Notation::Cxs), "ˈkaˌta");
+        assert_eq!(from_ipa("ˈkaˌta", Notation::XSampa), "\"ka%ta");
+        assert_eq!(from_ipa("ˈkaˌta ⱱ", Notation::Cxs), "'ka,ta b\\");
+        assert_eq!(from_ipa("kʲ", Notation::Cxs), "k_j");
+    }
+
+    #[test]
+    fn notations() {
+        assert!(matches!(Notation::parse(" X-SAMPA"), Ok(Notation::XSampa)));
+        assert!(matches!(Notation::parse("cxs"), Ok(Notation::Cxs)));
+        assert_eq!(Notation::parse("kirshenbaum").err().unwrap(), "Unknown notation: kirshenbaum. Use x-sampa or cxs");
+    }
+}